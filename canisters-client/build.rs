@@ -42,6 +42,8 @@ static DID_WHITELIST: LazyLock<HashSet<&str>> = LazyLock::new(|| {
     whitelist.insert("user_info_service");
     #[cfg(feature = "user-post-service")]
     whitelist.insert("user_post_service");
+    #[cfg(feature = "ckbtc-minter")]
+    whitelist.insert("ckbtc_minter");
 
     whitelist
 });
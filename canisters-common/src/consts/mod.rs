@@ -16,9 +16,16 @@ pub const GOBGOB_PROPIC_URL: &str = "https://imagedelivery.net/abXI9nS4DYYtyR1yF
 
 pub const CKBTC_LEDGER: &str = "mxzaz-hqaaa-aaaar-qaada-cai";
 pub const CKBTC_INDEX: &str = "n5wcd-faaaa-aaaar-qaaea-cai";
+/// ckBTC ledger transfer fee, in sats
+pub const CKBTC_FEE_SATS: u64 = 10;
 
 pub const CKUSDC_LEDGER: &str = "xevnm-gaaaa-aaaar-qafnq-cai";
 pub const CKUSDC_INDEX: &str = "xrs4b-hiaaa-aaaar-qafoa-cai";
+pub const CKUSDC_DECIMALS: u8 = 6;
+/// ckUSDC ledger transfer fee, in e6s (0.01 USDC)
+pub const CKUSDC_FEE_E6S: u64 = 10_000;
+
+pub const ICP_LEDGER_CANISTER: &str = "ryjl3-tyaaa-aaaaa-aaaba-cai";
 
 pub const DOLR_AI_LEDGER_CANISTER: &str = "6rdgd-kyaaa-aaaaq-aaavq-cai";
 
@@ -1,4 +1,5 @@
 pub mod posts;
+pub mod proposals;
 pub mod ref_history;
 pub mod token_roots;
 pub mod transaction;
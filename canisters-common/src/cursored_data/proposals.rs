@@ -0,0 +1,101 @@
+use std::sync::Mutex;
+
+use candid::Principal;
+use canisters_client::sns_governance::{ListProposals, ProposalId};
+
+use crate::{
+    utils::proposal::{ProposalDetails, ProposalStatus},
+    Canisters, Error,
+};
+
+use super::{CursoredDataProvider, KeyedData, PageEntry};
+
+impl KeyedData for ProposalDetails {
+    type Key = u64;
+
+    fn key(&self) -> Self::Key {
+        self.id
+    }
+}
+
+/// Lists proposals of an SNS, newest first, with the caller's ballots
+///
+/// Only goes forward and ignores start and end parameters when paginating
+///
+/// UB: Retrieving next page while the current page hasn't finished loading will lead to undefine behavior
+pub struct ProposalsProvider {
+    canisters: Canisters<true>,
+    governance: Principal,
+    /// only list proposals with these statuses, all if empty
+    include_status: Vec<ProposalStatus>,
+    // Mutex because we need to track the oldest seen proposal without mut ref.
+    before: Mutex<Option<u64>>,
+}
+
+impl Clone for ProposalsProvider {
+    fn clone(&self) -> Self {
+        let before = *self.before.lock().unwrap();
+
+        Self {
+            canisters: self.canisters.clone(),
+            governance: self.governance,
+            include_status: self.include_status.clone(),
+            before: Mutex::new(before),
+        }
+    }
+}
+
+impl ProposalsProvider {
+    pub fn new(canisters: Canisters<true>, governance: Principal) -> Self {
+        Self {
+            canisters,
+            governance,
+            include_status: vec![],
+            before: Mutex::new(None),
+        }
+    }
+
+    /// Only list proposals with the given statuses
+    pub fn with_status(mut self, include_status: Vec<ProposalStatus>) -> Self {
+        self.include_status = include_status;
+        self
+    }
+}
+
+impl CursoredDataProvider for ProposalsProvider {
+    type Data = ProposalDetails;
+    type Error = Error;
+
+    async fn get_by_cursor_inner(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<PageEntry<Self::Data>, Self::Error> {
+        let limit = end - start;
+        let before_proposal = self.before.lock().unwrap().map(|id| ProposalId { id });
+
+        let governance = self.canisters.sns_governance(self.governance).await;
+        let proposals = governance
+            .list_proposals(ListProposals {
+                include_reward_status: vec![],
+                before_proposal,
+                limit: limit as u32,
+                exclude_type: vec![],
+                include_status: self.include_status.iter().map(|s| s.as_i32()).collect(),
+            })
+            .await?
+            .proposals;
+
+        let end = proposals.len() < limit;
+        let data: Vec<_> = proposals
+            .into_iter()
+            .filter_map(ProposalDetails::from_proposal_data)
+            .collect();
+
+        if let Some(last) = data.last() {
+            *self.before.lock().unwrap() = Some(last.id);
+        }
+
+        Ok(PageEntry { data, end })
+    }
+}
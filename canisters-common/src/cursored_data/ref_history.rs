@@ -1,6 +1,11 @@
+use std::sync::Mutex;
+
 use candid::Principal;
+use hon_worker_common::{HonWorkerClient, PaginatedReferralsReq, ReferralItem};
+
+use crate::{Error, HonError};
 
-use super::KeyedData;
+use super::{CursoredDataProvider, KeyedData, PageEntry};
 
 #[derive(Clone, Copy)]
 pub struct HistoryDetails {
@@ -16,3 +21,86 @@ impl KeyedData for HistoryDetails {
         self.referee
     }
 }
+
+impl From<ReferralItem> for HistoryDetails {
+    fn from(value: ReferralItem) -> Self {
+        Self {
+            epoch_secs: value.created_at,
+            referee: value.referee,
+            amount: value.amount,
+        }
+    }
+}
+
+/// Referrals made by a user, fetched from the hot-or-not worker
+///
+/// Only goes forward and ignores start and end parameters when paginating
+///
+/// UB: Retrieving next page while the current page hasn't finished loading will lead to undefine behavior
+pub struct ReferralHistoryProvider {
+    // Mutex because we need to track the cursor internally without mut ref.
+    cursor: Mutex<Option<u64>>,
+    user_principal: Principal,
+    client: HonWorkerClient,
+}
+
+// impl clone by hand because Mutex<T> doesn't impl clone on its own
+impl Clone for ReferralHistoryProvider {
+    fn clone(&self) -> Self {
+        let cursor = *self.cursor.lock().unwrap();
+
+        Self {
+            cursor: Mutex::new(cursor),
+            user_principal: self.user_principal,
+            client: self.client.clone(),
+        }
+    }
+}
+
+impl ReferralHistoryProvider {
+    pub fn new(user_principal: Principal) -> Self {
+        Self {
+            cursor: Mutex::new(None),
+            user_principal,
+            client: HonWorkerClient::default(),
+        }
+    }
+
+    /// Use `worker` instead of the client for [`hon_worker_common::WORKER_URL`]
+    /// e.g. to test against a local worker
+    pub fn with_worker(mut self, worker: HonWorkerClient) -> Self {
+        self.client = worker;
+        self
+    }
+}
+
+impl CursoredDataProvider for ReferralHistoryProvider {
+    type Data = HistoryDetails;
+    type Error = Error;
+
+    async fn get_by_cursor_inner(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<PageEntry<Self::Data>, Self::Error> {
+        let cursor = *self.cursor.lock().unwrap();
+        let req = PaginatedReferralsReq {
+            cursor,
+            limit: (end - start) as u64,
+        };
+
+        let res = self
+            .client
+            .referral_history(self.user_principal, &req)
+            .await
+            .map_err(HonError::from)?;
+
+        let end = res.cursor.is_none();
+        *self.cursor.lock().unwrap() = res.cursor;
+
+        Ok(PageEntry {
+            data: res.items.into_iter().map(HistoryDetails::from).collect(),
+            end,
+        })
+    }
+}
@@ -5,12 +5,13 @@ use canisters_client::{
     sns_index::{self, GetAccountTransactionsArgs, GetTransactionsResult},
     sns_ledger,
 };
+use futures_util::future::try_join_all;
 use ic_agent::hash_tree::{HashTree, LookupResult};
 
 use crate::{
     utils::{
         token::balance::TokenBalance,
-        transaction::{TxnInfoType, TxnInfoWallet},
+        transaction::{merge_txn_histories, TokenTxnInfo, TxnFilter, TxnInfoType, TxnInfoWallet},
     },
     Canisters, Error, Result,
 };
@@ -25,6 +26,14 @@ impl KeyedData for TxnInfoWallet {
     }
 }
 
+impl KeyedData for TokenTxnInfo {
+    type Key = (Principal, u64);
+
+    fn key(&self) -> Self::Key {
+        (self.token, self.txn.id)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum IndexOrLedger {
     Index {
@@ -42,6 +51,37 @@ pub struct TxnHistory {
 }
 
 impl TxnHistory {
+    /// Canister the transactions are fetched from
+    pub fn token(&self) -> Principal {
+        match self.source {
+            IndexOrLedger::Index { index, .. } => index,
+            IndexOrLedger::Ledger(ledger) => ledger,
+        }
+    }
+
+    pub fn filtered(self, filter: TxnFilter) -> FilteredTxnHistory {
+        FilteredTxnHistory {
+            history: self,
+            filter,
+        }
+    }
+
+    /// Fetch the entire history, newest first, `page_size` transactions at a time
+    pub async fn fetch_all(&self, page_size: usize) -> Result<Vec<TxnInfoWallet>> {
+        let mut txns = Vec::new();
+        let mut start = 0;
+        loop {
+            let page = self.get_by_cursor(start, start + page_size).await?;
+            txns.extend(page.data);
+            if page.end {
+                break;
+            }
+            start += page_size;
+        }
+
+        Ok(txns)
+    }
+
     fn parse_transactions_ledger(
         txn: sns_ledger::Transaction,
         id: u64,
@@ -186,7 +226,7 @@ impl TxnHistory {
         Ok(PageEntry { data, end: is_end })
     }
 
-    async fn get_latest_ledger_transaction(&self, ledger_id: Principal) -> Result<u64> {
+    pub(crate) async fn get_latest_ledger_transaction(&self, ledger_id: Principal) -> Result<u64> {
         let ledger = self.canisters.sns_ledger(ledger_id).await;
         let tip_certificate = ledger
             .icrc_3_get_tip_certificate()
@@ -273,3 +313,48 @@ impl CursoredDataProvider for TxnHistory {
         }
     }
 }
+
+/// [`TxnHistory`] that only yields transactions matching a [`TxnFilter`]
+///
+/// pages may contain fewer entries than requested
+#[derive(Clone)]
+pub struct FilteredTxnHistory {
+    pub history: TxnHistory,
+    pub filter: TxnFilter,
+}
+
+impl CursoredDataProvider for FilteredTxnHistory {
+    type Data = TxnInfoWallet;
+    type Error = Error;
+
+    async fn get_by_cursor_inner(&self, start: usize, end: usize) -> Result<PageEntry<Self::Data>> {
+        let PageEntry { data, end } = self.history.get_by_cursor_inner(start, end).await?;
+
+        Ok(PageEntry {
+            data: data
+                .into_iter()
+                .filter(|txn| self.filter.matches(txn))
+                .collect(),
+            end,
+        })
+    }
+}
+
+/// Fetch the full history of every token and merge them, newest first
+/// e.g. for exporting with [`crate::utils::transaction::export_txns_csv`]
+pub async fn fetch_merged_txn_history(
+    histories: &[TxnHistory],
+    filter: &TxnFilter,
+    page_size: usize,
+) -> Result<Vec<TokenTxnInfo>> {
+    let fetched = try_join_all(histories.iter().map(|history| async move {
+        let txns = history.fetch_all(page_size).await?;
+        Ok::<_, Error>((
+            history.token(),
+            txns.into_iter().filter(|txn| filter.matches(txn)).collect(),
+        ))
+    }))
+    .await?;
+
+    Ok(merge_txn_histories(fetched))
+}
@@ -2,13 +2,13 @@ use std::sync::Mutex;
 
 use candid::Principal;
 use hon_worker_common::{
-    GameRes, GameResV2, GameResV3, GameResV4WithCanister, PaginatedGamesReq, PaginatedGamesRes,
-    PaginatedGamesResV2, PaginatedGamesResV3, PaginatedGamesResV4, WORKER_URL,
+    GameRecord, GameRes, GameResV2, GameResV3, GameResV4WithCanister, HonWorkerClient,
+    PaginatedGamesReq, PaginatedGamesRes, PaginatedGamesResV2, PaginatedGamesResV3,
+    PaginatedGamesResV4,
 };
-use url::Url;
 use yral_metadata_client::MetadataClient;
 
-use crate::{utils::vote::VoteDetails, Error};
+use crate::{utils::vote::VoteDetails, Error, HonError};
 
 use super::{CursoredDataProvider, KeyedData, PageEntry};
 
@@ -36,6 +36,17 @@ impl KeyedData for GameResV4WithCanister {
     }
 }
 
+impl KeyedData for GameRecord {
+    type Key = (Option<Principal>, String);
+
+    fn key(&self) -> Self::Key {
+        (
+            self.post_canister.or(self.publisher_principal),
+            self.post_id.clone(),
+        )
+    }
+}
+
 /// Only goes forward and ignores start and end parameters when paginating
 ///
 /// UB: Retrieving next page while the current page hasn't finished loading will lead to undefine behavior
@@ -43,6 +54,7 @@ pub struct VotesWithSatsProvider {
     // Mutex because we need to track next internally without mut ref.
     next: Mutex<Option<String>>,
     user_principal: Principal,
+    worker: HonWorkerClient,
 }
 
 // impl clone by hand because Mutex<T> doesn't impl clone on its own
@@ -55,12 +67,13 @@ impl Clone for VotesWithSatsProvider {
         Self {
             next: Mutex::new(next),
             user_principal,
+            worker: self.worker.clone(),
         }
     }
 }
 
 impl CursoredDataProvider for VotesWithSatsProvider {
-    type Data = GameRes;
+    type Data = GameRecord;
     type Error = Error;
 
     async fn get_by_cursor_inner(
@@ -68,27 +81,26 @@ impl CursoredDataProvider for VotesWithSatsProvider {
         start: usize,
         end: usize,
     ) -> Result<PageEntry<Self::Data>, Self::Error> {
-        // TODO make lazy static.
-        // i tried earlier, but there was some wasm related error?
-        // give it another shot in isloation
-        let url: Url = WORKER_URL.parse().unwrap();
-        let path = format!("/games/{}", self.user_principal);
-        let url = url.join(&path).unwrap();
         let cursor = self.get_cursor();
         let req = PaginatedGamesReq {
             page_size: end - start,
             cursor,
         };
 
-        let client = reqwest::Client::new();
-        let PaginatedGamesRes { games, next }: PaginatedGamesRes =
-            client.post(url).json(&req).send().await?.json().await?;
+        let PaginatedGamesRes { games, next } = self
+            .worker
+            .games(self.user_principal, &req)
+            .await
+            .map_err(HonError::from)?;
 
         let end = next.is_none();
 
         *self.next.lock().unwrap() = next;
 
-        Ok(PageEntry { data: games, end })
+        Ok(PageEntry {
+            data: games.into_iter().map(GameRecord::from).collect(),
+            end,
+        })
     }
 }
 
@@ -97,9 +109,17 @@ impl VotesWithSatsProvider {
         Self {
             user_principal,
             next: Mutex::new(None),
+            worker: HonWorkerClient::default(),
         }
     }
 
+    /// Use `worker` instead of the client for [`hon_worker_common::WORKER_URL`]
+    /// e.g. to test against a local worker
+    pub fn with_worker(mut self, worker: HonWorkerClient) -> Self {
+        self.worker = worker;
+        self
+    }
+
     fn get_cursor(&self) -> Option<String> {
         self.next.lock().unwrap().clone()
     }
@@ -128,6 +148,7 @@ pub struct VotesWithSatsProviderV2 {
     // Mutex because we need to track next internally without mut ref.
     next: Mutex<Option<String>>,
     user_principal: Principal,
+    worker: HonWorkerClient,
 }
 
 // impl clone by hand because Mutex<T> doesn't impl clone on its own
@@ -140,12 +161,13 @@ impl Clone for VotesWithSatsProviderV2 {
         Self {
             next: Mutex::new(next),
             user_principal,
+            worker: self.worker.clone(),
         }
     }
 }
 
 impl CursoredDataProvider for VotesWithSatsProviderV2 {
-    type Data = GameResV2;
+    type Data = GameRecord;
     type Error = Error;
 
     async fn get_by_cursor_inner(
@@ -153,27 +175,26 @@ impl CursoredDataProvider for VotesWithSatsProviderV2 {
         start: usize,
         end: usize,
     ) -> Result<PageEntry<Self::Data>, Self::Error> {
-        // TODO make lazy static.
-        // i tried earlier, but there was some wasm related error?
-        // give it another shot in isloation
-        let url: Url = WORKER_URL.parse().unwrap();
-        let path = format!("/games/{}", self.user_principal);
-        let url = url.join(&path).unwrap();
         let cursor = self.get_cursor();
         let req = PaginatedGamesReq {
             page_size: end - start,
             cursor,
         };
 
-        let client = reqwest::Client::new();
-        let PaginatedGamesResV2 { games, next }: PaginatedGamesResV2 =
-            client.post(url).json(&req).send().await?.json().await?;
+        let PaginatedGamesResV2 { games, next } = self
+            .worker
+            .games_v2(self.user_principal, &req)
+            .await
+            .map_err(HonError::from)?;
 
         let end = next.is_none();
 
         *self.next.lock().unwrap() = next;
 
-        Ok(PageEntry { data: games, end })
+        Ok(PageEntry {
+            data: games.into_iter().map(GameRecord::from).collect(),
+            end,
+        })
     }
 }
 
@@ -182,19 +203,28 @@ impl VotesWithSatsProviderV2 {
         Self {
             user_principal,
             next: Mutex::new(None),
+            worker: HonWorkerClient::default(),
         }
     }
 
+    /// Use `worker` instead of the client for [`hon_worker_common::WORKER_URL`]
+    /// e.g. to test against a local worker
+    pub fn with_worker(mut self, worker: HonWorkerClient) -> Self {
+        self.worker = worker;
+        self
+    }
+
     fn get_cursor(&self) -> Option<String> {
         self.next.lock().unwrap().clone()
     }
 }
 
-/// VotesWithSatsProviderV3 calls the /v3 API and converts GameResV3 to GameRes using metadata client
+/// VotesWithSatsProviderV3 calls the /v3 API and resolves the post canisters using metadata client
 pub struct VotesWithSatsProviderV3 {
     next: Mutex<Option<String>>,
     user_principal: Principal,
     metadata_client: MetadataClient<false>,
+    worker: HonWorkerClient,
 }
 
 impl Clone for VotesWithSatsProviderV3 {
@@ -208,12 +238,13 @@ impl Clone for VotesWithSatsProviderV3 {
             next: Mutex::new(next),
             user_principal,
             metadata_client,
+            worker: self.worker.clone(),
         }
     }
 }
 
 impl CursoredDataProvider for VotesWithSatsProviderV3 {
-    type Data = GameRes;
+    type Data = GameRecord;
     type Error = Error;
 
     async fn get_by_cursor_inner(
@@ -221,23 +252,22 @@ impl CursoredDataProvider for VotesWithSatsProviderV3 {
         start: usize,
         end: usize,
     ) -> Result<PageEntry<Self::Data>, Self::Error> {
-        let url: Url = WORKER_URL.parse().unwrap();
-        let path = format!("/v3/games/{}", self.user_principal);
-        let url = url.join(&path).unwrap();
         let cursor = self.get_cursor();
         let req = PaginatedGamesReq {
             page_size: end - start,
             cursor,
         };
 
-        let client = reqwest::Client::new();
-        let PaginatedGamesResV3 { games, next }: PaginatedGamesResV3 =
-            client.post(url).json(&req).send().await?.json().await?;
+        let PaginatedGamesResV3 { games, next } = self
+            .worker
+            .games_v3(self.user_principal, &req)
+            .await
+            .map_err(HonError::from)?;
 
         let end = next.is_none();
         *self.next.lock().unwrap() = next;
 
-        // Convert GameResV3 to GameRes using metadata client
+        // Resolve post canisters using metadata client
         let mut converted_games = Vec::new();
         let publisher_principals: Vec<Principal> =
             games.iter().map(|g| g.publisher_principal).collect();
@@ -250,12 +280,8 @@ impl CursoredDataProvider for VotesWithSatsProviderV3 {
 
         for game_v3 in games {
             if let Some(Some(metadata)) = canister_mappings.get(&game_v3.publisher_principal) {
-                let game_res = GameRes {
-                    post_canister: metadata.user_canister_id,
-                    post_id: game_v3.post_id,
-                    game_info: game_v3.game_info,
-                };
-                converted_games.push(game_res);
+                let game = GameRecord::from(game_v3).with_post_canister(metadata.user_canister_id);
+                converted_games.push(game);
             }
         }
 
@@ -270,6 +296,7 @@ pub struct VotesWithSatsProviderV4 {
     next: Mutex<Option<String>>,
     user_principal: Principal,
     metadata_client: MetadataClient<false>,
+    worker: HonWorkerClient,
 }
 
 impl VotesWithSatsProviderV4 {
@@ -278,9 +305,17 @@ impl VotesWithSatsProviderV4 {
             user_principal,
             metadata_client,
             next: Mutex::new(None),
+            worker: HonWorkerClient::default(),
         }
     }
 
+    /// Use `worker` instead of the client for [`hon_worker_common::WORKER_URL`]
+    /// e.g. to test against a local worker
+    pub fn with_worker(mut self, worker: HonWorkerClient) -> Self {
+        self.worker = worker;
+        self
+    }
+
     fn get_cursor(&self) -> Option<String> {
         self.next.lock().unwrap().clone()
     }
@@ -297,12 +332,13 @@ impl Clone for VotesWithSatsProviderV4 {
             next: Mutex::new(next),
             user_principal,
             metadata_client,
+            worker: self.worker.clone(),
         }
     }
 }
 
 impl CursoredDataProvider for VotesWithSatsProviderV4 {
-    type Data = GameResV4WithCanister;
+    type Data = GameRecord;
     type Error = Error;
 
     async fn get_by_cursor_inner(
@@ -310,23 +346,22 @@ impl CursoredDataProvider for VotesWithSatsProviderV4 {
         start: usize,
         end: usize,
     ) -> Result<PageEntry<Self::Data>, Self::Error> {
-        let url: Url = WORKER_URL.parse().unwrap();
-        let path = format!("/v4/games/{}", self.user_principal);
-        let url = url.join(&path).unwrap();
         let cursor = self.get_cursor();
         let req = PaginatedGamesReq {
             page_size: end - start,
             cursor,
         };
 
-        let client = reqwest::Client::new();
-        let PaginatedGamesResV4 { games, next }: PaginatedGamesResV4 =
-            client.post(url).json(&req).send().await?.json().await?;
+        let PaginatedGamesResV4 { games, next } = self
+            .worker
+            .games_v4(self.user_principal, &req)
+            .await
+            .map_err(HonError::from)?;
 
         let end = next.is_none();
         *self.next.lock().unwrap() = next;
 
-        // Convert GameResV3 to GameRes using metadata client
+        // Resolve post canisters using metadata client
         let mut converted_games = Vec::new();
         let publisher_principals: Vec<Principal> =
             games.iter().map(|g| g.publisher_principal).collect();
@@ -339,12 +374,8 @@ impl CursoredDataProvider for VotesWithSatsProviderV4 {
 
         for game_v4 in games {
             if let Some(Some(metadata)) = canister_mappings.get(&game_v4.publisher_principal) {
-                let game_res = GameResV4WithCanister {
-                    post_creator_canister: metadata.user_canister_id,
-                    post_id: game_v4.post_id.clone(),
-                    game_info: game_v4.game_info,
-                };
-                converted_games.push(game_res);
+                let game = GameRecord::from(game_v4).with_post_canister(metadata.user_canister_id);
+                converted_games.push(game);
             }
         }
 
@@ -361,9 +392,17 @@ impl VotesWithSatsProviderV3 {
             user_principal,
             metadata_client,
             next: Mutex::new(None),
+            worker: HonWorkerClient::default(),
         }
     }
 
+    /// Use `worker` instead of the client for [`hon_worker_common::WORKER_URL`]
+    /// e.g. to test against a local worker
+    pub fn with_worker(mut self, worker: HonWorkerClient) -> Self {
+        self.worker = worker;
+        self
+    }
+
     fn get_cursor(&self) -> Option<String> {
         self.next.lock().unwrap().clone()
     }
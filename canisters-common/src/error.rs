@@ -1,14 +1,21 @@
 use std::{io, str::FromStr};
 
-use candid::Nat;
+use candid::{Nat, Principal};
+use canisters_client::{
+    ckbtc_minter::RetrieveBtcWithApprovalError, sns_governance::GovernanceError,
+};
 use thiserror::Error;
 
+use crate::utils::swap::SwapLifecycle;
+
 #[derive(Debug, Error)]
 pub enum PndError {
     #[error("worker didn't return a number: {0}")]
     Parse(<Nat as FromStr>::Err),
     #[error("network error when accessing worker: {0}")]
     Network(#[from] reqwest::Error),
+    #[error("{0}")]
+    Worker(#[from] hon_worker_common::HonWorkerClientError),
 }
 
 #[derive(Debug, Error)]
@@ -17,6 +24,136 @@ pub enum HonError {
     Network(#[from] reqwest::Error),
     #[error("error accessing game backend: {0}")]
     Backend(String),
+    #[error("{0}")]
+    Worker(#[from] hon_worker_common::WorkerError),
+    #[error("{0}")]
+    Client(#[from] hon_worker_common::HonWorkerClientError),
+}
+
+impl HonError {
+    /// Parse an error response from the worker, falling back to the raw body
+    pub fn from_worker_response(body: String) -> Self {
+        hon_worker_common::WorkerError::from_response_body(&body)
+            .map(Self::Worker)
+            .unwrap_or(Self::Backend(body))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SnsGovernanceError {
+    #[error("governance rejected the request ({error_type}): {message}")]
+    Governance { error_type: i32, message: String },
+    #[error("failed to transfer neuron stake: {0}")]
+    StakeTransfer(String),
+    #[error("unexpected response from governance")]
+    UnexpectedResponse,
+}
+
+impl From<GovernanceError> for SnsGovernanceError {
+    fn from(value: GovernanceError) -> Self {
+        Self::Governance {
+            error_type: value.error_type,
+            message: value.error_message,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SwapError {
+    #[error("swap canister not found for {0}")]
+    SwapNotFound(Principal),
+    #[error("swap has no sale parameters")]
+    MissingParams,
+    #[error("swap is not accepting participation ({0:?})")]
+    NotOpen(SwapLifecycle),
+    #[error("participation must be between {min} and {max} ICP e8s")]
+    InvalidAmount { min: u64, max: u64 },
+    #[error(
+        "participation would exceed the maximum of {max} ICP e8s, {accepted} already accepted"
+    )]
+    MaxParticipationExceeded { accepted: u64, max: u64 },
+    #[error("failed to transfer ICP to swap: {0}")]
+    Transfer(String),
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TokenBalanceError {
+    #[error("decimals mismatch: {left} != {right}")]
+    DecimalsMismatch { left: u8, right: u8 },
+    #[error("balance underflow")]
+    Underflow,
+    #[error("rescaling to {0} decimals loses precision")]
+    PrecisionLoss(u8),
+}
+
+#[derive(Debug, Error)]
+pub enum CertificationError {
+    #[error("certificate verification failed: {0}")]
+    Certificate(String),
+    #[error("certified data does not match the hash tree")]
+    CertifiedDataMismatch,
+    #[error("hash tree is missing `{0}`")]
+    MissingLabel(&'static str),
+    #[error("block {0} does not match the hash chain")]
+    HashMismatch(u64),
+    #[error("block {0} is missing from the ledger response")]
+    MissingBlock(u64),
+    #[error("blocks from {0} are archived")]
+    Archived(u64),
+    #[error("block is not a valid ICRC-3 block")]
+    MalformedBlock,
+}
+
+#[derive(Debug, Error)]
+pub enum RetrieveBtcError {
+    #[error("failed to approve ckBTC for the minter: {0}")]
+    Approve(String),
+    #[error("invalid bitcoin address: {0}")]
+    MalformedAddress(String),
+    #[error("another withdrawal is already being processed")]
+    AlreadyProcessing,
+    #[error("withdrawal amount is too low, minimum is {min_sats} sats")]
+    AmountTooLow { min_sats: u64 },
+    #[error("insufficient ckBTC balance: {balance_sats} sats")]
+    InsufficientFunds { balance_sats: u64 },
+    #[error("insufficient allowance for the minter: {allowance_sats} sats")]
+    InsufficientAllowance { allowance_sats: u64 },
+    #[error("minter is temporarily unavailable: {0}")]
+    TemporarilyUnavailable(String),
+    #[error("minter error ({code}): {message}")]
+    Generic { code: u64, message: String },
+}
+
+impl From<RetrieveBtcWithApprovalError> for RetrieveBtcError {
+    fn from(value: RetrieveBtcWithApprovalError) -> Self {
+        match value {
+            RetrieveBtcWithApprovalError::MalformedAddress(address) => {
+                Self::MalformedAddress(address)
+            }
+            RetrieveBtcWithApprovalError::AlreadyProcessing => Self::AlreadyProcessing,
+            RetrieveBtcWithApprovalError::AmountTooLow(min_sats) => Self::AmountTooLow { min_sats },
+            RetrieveBtcWithApprovalError::InsufficientFunds { balance } => {
+                Self::InsufficientFunds {
+                    balance_sats: balance,
+                }
+            }
+            RetrieveBtcWithApprovalError::InsufficientAllowance { allowance } => {
+                Self::InsufficientAllowance {
+                    allowance_sats: allowance,
+                }
+            }
+            RetrieveBtcWithApprovalError::TemporarilyUnavailable(msg) => {
+                Self::TemporarilyUnavailable(msg)
+            }
+            RetrieveBtcWithApprovalError::GenericError {
+                error_message,
+                error_code,
+            } => Self::Generic {
+                code: error_code,
+                message: error_message,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -40,15 +177,29 @@ pub enum Error {
     #[error("invalid tip certificate in ledger")]
     TipCertificate,
     #[error("{0}")]
+    Certification(#[from] CertificationError),
+    #[error("{0}")]
+    TokenBalance(#[from] TokenBalanceError),
+    #[error("{0}")]
     CborDe(#[from] ciborium::de::Error<io::Error>),
     #[error("{0}")]
     PndError(#[from] PndError),
     #[error("{0}")]
     Hon(#[from] HonError),
     #[error("{0}")]
+    SnsGovernance(#[from] SnsGovernanceError),
+    #[error("{0}")]
+    Swap(#[from] SwapError),
+    #[error("{0}")]
+    RetrieveBtc(#[from] RetrieveBtcError),
+    #[error("{0}")]
     Url(#[from] url::ParseError),
     #[error("network error: {0}")]
     Network(#[from] reqwest::Error),
+    #[error("failed to fetch token price: {0}")]
+    Price(#[from] price_oracle::Error),
+    #[error("system randomness unavailable: {0}")]
+    Random(#[from] getrandom::Error),
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use agent_wrapper::AgentWrapper;
 use candid::Principal;
 use canisters_client::{
+    ckbtc_minter::CkbtcMinter,
     ic::USER_POST_SERVICE_ID,
     local::USER_INFO_SERVICE_ID,
     post_cache::PostCache,
@@ -16,7 +17,7 @@ use canisters_client::{
     user_post_service::UserPostService,
 };
 use consts::{
-    canister_ids::{POST_CACHE_ID, RATE_LIMITS_ID},
+    canister_ids::{CKBTC_MINTER_ID, POST_CACHE_ID, RATE_LIMITS_ID},
     METADATA_API_BASE,
 };
 use ic_agent::{identity::DelegatedIdentity, Identity};
@@ -385,6 +386,11 @@ impl<const A: bool> Canisters<A> {
         let agent = self.agent.get_agent().await;
         RateLimits(RATE_LIMITS_ID, agent)
     }
+
+    pub async fn ckbtc_minter(&self) -> CkbtcMinter<'_> {
+        let agent = self.agent.get_agent().await;
+        CkbtcMinter(CKBTC_MINTER_ID, agent)
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -0,0 +1,172 @@
+use candid::Principal;
+use canisters_client::{
+    ckbtc_minter::{
+        EstimateWithdrawalFeeArgs, RetrieveBtcStatusRequest, RetrieveBtcStatusV2,
+        RetrieveBtcWithApprovalArgs, RetrieveBtcWithApprovalResult,
+    },
+    sns_ledger::{self, Account as LedgerAccount},
+};
+use serde::{Deserialize, Serialize};
+use web_time::Duration;
+
+use crate::{
+    consts::{canister_ids::CKBTC_MINTER_ID, CKBTC_FEE_SATS, CKBTC_LEDGER},
+    Canisters, Result, RetrieveBtcError,
+};
+
+use super::time::sleep;
+
+pub const BTC_WITHDRAWAL_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Progress of a BTC withdrawal, simplified from the minter's status
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum BtcWithdrawalStatus {
+    /// The minter has no record of the withdrawal
+    Unknown,
+    /// The minter is building and signing the bitcoin transaction
+    Pending,
+    /// The bitcoin transaction was sent, `txid` is in bitcoin byte order
+    Submitted {
+        txid: Vec<u8>,
+    },
+    Confirmed {
+        txid: Vec<u8>,
+    },
+    /// The amount didn't cover the bitcoin fees
+    AmountTooLow,
+    /// The ckBTC was or will be returned to the user
+    Reimbursed,
+}
+
+impl BtcWithdrawalStatus {
+    /// Whether the status will not change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Confirmed { .. } | Self::AmountTooLow | Self::Reimbursed
+        )
+    }
+}
+
+impl From<RetrieveBtcStatusV2> for BtcWithdrawalStatus {
+    fn from(value: RetrieveBtcStatusV2) -> Self {
+        match value {
+            RetrieveBtcStatusV2::Unknown => Self::Unknown,
+            RetrieveBtcStatusV2::Pending
+            | RetrieveBtcStatusV2::Signing
+            | RetrieveBtcStatusV2::Sending { .. } => Self::Pending,
+            RetrieveBtcStatusV2::Submitted { txid } => Self::Submitted {
+                txid: txid.into_vec(),
+            },
+            RetrieveBtcStatusV2::Confirmed { txid } => Self::Confirmed {
+                txid: txid.into_vec(),
+            },
+            RetrieveBtcStatusV2::AmountTooLow => Self::AmountTooLow,
+            RetrieveBtcStatusV2::Reimbursed(_) | RetrieveBtcStatusV2::WillReimburse(_) => {
+                Self::Reimbursed
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct BtcWithdrawalFee {
+    pub bitcoin_fee_sats: u64,
+    pub minter_fee_sats: u64,
+}
+
+impl BtcWithdrawalFee {
+    pub fn total_sats(&self) -> u64 {
+        self.bitcoin_fee_sats + self.minter_fee_sats
+    }
+}
+
+impl<const A: bool> Canisters<A> {
+    /// Fees deducted from a withdrawal of `amount_sats`
+    pub async fn estimate_btc_withdrawal_fee(&self, amount_sats: u64) -> Result<BtcWithdrawalFee> {
+        let minter = self.ckbtc_minter().await;
+        let fee = minter
+            .estimate_withdrawal_fee(EstimateWithdrawalFeeArgs {
+                amount: Some(amount_sats),
+            })
+            .await?;
+
+        Ok(BtcWithdrawalFee {
+            bitcoin_fee_sats: fee.bitcoin_fee,
+            minter_fee_sats: fee.minter_fee,
+        })
+    }
+
+    /// Status of the withdrawal burned in ckBTC ledger block `block_index`
+    pub async fn btc_withdrawal_status(&self, block_index: u64) -> Result<BtcWithdrawalStatus> {
+        let minter = self.ckbtc_minter().await;
+        let status = minter
+            .retrieve_btc_status_v_2(RetrieveBtcStatusRequest { block_index })
+            .await?;
+
+        Ok(status.into())
+    }
+
+    /// Poll the status of the withdrawal every `interval` until it is final
+    /// or `max_polls` polls were made, returns the last status seen
+    pub async fn wait_for_btc_withdrawal(
+        &self,
+        block_index: u64,
+        interval: Duration,
+        max_polls: usize,
+    ) -> Result<BtcWithdrawalStatus> {
+        let mut status = self.btc_withdrawal_status(block_index).await?;
+        for _ in 1..max_polls {
+            if status.is_final() {
+                break;
+            }
+            sleep(interval).await;
+            status = self.btc_withdrawal_status(block_index).await?;
+        }
+
+        Ok(status)
+    }
+}
+
+impl Canisters<true> {
+    /// Withdraw `amount_sats` of the user's ckBTC to the bitcoin `address`
+    ///
+    /// approves the minter to burn `amount_sats` (plus the ledger fee), then requests the withdrawal
+    /// returns the index of the burn block, to be used with [`Self::btc_withdrawal_status`]
+    pub async fn withdraw_btc(&self, address: String, amount_sats: u64) -> Result<u64> {
+        let ledger_id = Principal::from_text(CKBTC_LEDGER).unwrap();
+        let ledger = self.sns_ledger(ledger_id).await;
+        let approve_res = ledger
+            .icrc_2_approve(sns_ledger::ApproveArgs {
+                fee: None,
+                memo: None,
+                from_subaccount: None,
+                created_at_time: None,
+                amount: (amount_sats + CKBTC_FEE_SATS).into(),
+                expected_allowance: None,
+                expires_at: None,
+                spender: LedgerAccount {
+                    owner: CKBTC_MINTER_ID,
+                    subaccount: None,
+                },
+            })
+            .await?;
+        if let sns_ledger::ApproveResult::Err(e) = approve_res {
+            return Err(RetrieveBtcError::Approve(format!("{e:?}")).into());
+        }
+
+        let minter = self.ckbtc_minter().await;
+        let res = minter
+            .retrieve_btc_with_approval(RetrieveBtcWithApprovalArgs {
+                address,
+                amount: amount_sats,
+                from_subaccount: None,
+            })
+            .await?;
+
+        match res {
+            RetrieveBtcWithApprovalResult::Ok(ok) => Ok(ok.block_index),
+            RetrieveBtcWithApprovalResult::Err(e) => Err(RetrieveBtcError::from(e).into()),
+        }
+    }
+}
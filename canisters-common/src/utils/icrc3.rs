@@ -0,0 +1,339 @@
+use std::io::Cursor;
+
+use candid::{Int, Nat, Principal};
+use canisters_client::sns_ledger::{GetBlocksArgs, Icrc3Value};
+use ic_agent::{
+    hash_tree::{HashTree, LookupResult},
+    Certificate,
+};
+use num_bigint::{BigInt, BigUint};
+use sha2::{Digest, Sha256};
+
+use crate::{Canisters, CertificationError, Error, Result};
+
+const BLOCKS_PAGE_SIZE: u64 = 100;
+
+/// Tip of a ledger's block log, as certified by the IC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CertifiedTip {
+    pub last_block_index: u64,
+    pub last_block_hash: [u8; 32],
+}
+
+/// A block whose hash is chained to a [`CertifiedTip`]
+#[derive(Clone, Debug)]
+pub struct VerifiedBlock {
+    pub index: u64,
+    pub hash: [u8; 32],
+    pub block: Icrc3Value,
+}
+
+fn leb128_nat(n: &BigUint) -> Vec<u8> {
+    let mut n = n.clone();
+    let mut out = Vec::new();
+    loop {
+        let byte = u8::try_from(&n & BigUint::from(0x7fu8)).unwrap();
+        n >>= 7;
+        if n.bits() == 0 {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn leb128_int(i: &BigInt) -> Vec<u8> {
+    let mut i = i.clone();
+    let mut out = Vec::new();
+    loop {
+        // `&` works on the two's complement representation and `>>=` rounds towards negative infinity
+        let byte = u8::try_from(&i & BigInt::from(0x7f)).unwrap();
+        i >>= 7;
+        let done = (i == BigInt::from(0) && byte & 0x40 == 0)
+            || (i == BigInt::from(-1) && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Representation independent hash of an ICRC-3 value
+pub fn icrc3_value_hash(value: &Icrc3Value) -> [u8; 32] {
+    match value {
+        Icrc3Value::Blob(b) => Sha256::digest(b).into(),
+        Icrc3Value::Text(t) => Sha256::digest(t.as_bytes()).into(),
+        Icrc3Value::Nat(Nat(n)) => Sha256::digest(leb128_nat(n)).into(),
+        Icrc3Value::Int(Int(i)) => Sha256::digest(leb128_int(i)).into(),
+        Icrc3Value::Array(items) => {
+            let mut hasher = Sha256::new();
+            for item in items {
+                hasher.update(icrc3_value_hash(item));
+            }
+            hasher.finalize().into()
+        }
+        Icrc3Value::Map(entries) => {
+            let mut pairs: Vec<Vec<u8>> = entries
+                .iter()
+                .map(|(k, v)| {
+                    let mut pair = Sha256::digest(k.as_bytes()).to_vec();
+                    pair.extend_from_slice(&icrc3_value_hash(v));
+                    pair
+                })
+                .collect();
+            pairs.sort();
+
+            let mut hasher = Sha256::new();
+            for pair in pairs {
+                hasher.update(pair);
+            }
+            hasher.finalize().into()
+        }
+    }
+}
+
+/// Hash of the parent block, `None` for the first block
+fn parent_hash(block: &Icrc3Value) -> Result<Option<[u8; 32]>, CertificationError> {
+    let Icrc3Value::Map(entries) = block else {
+        return Err(CertificationError::MalformedBlock);
+    };
+    let Some((_, phash)) = entries.iter().find(|(k, _)| k == "phash") else {
+        return Ok(None);
+    };
+    let phash: &Icrc3Value = phash;
+    match phash {
+        Icrc3Value::Blob(b) => b
+            .as_slice()
+            .try_into()
+            .map(Some)
+            .map_err(|_| CertificationError::MalformedBlock),
+        _ => Err(CertificationError::MalformedBlock),
+    }
+}
+
+fn lookup_label<'a>(
+    tree: &'a HashTree<Vec<u8>>,
+    label: &'static str,
+) -> Result<&'a [u8], CertificationError> {
+    match tree.lookup_path([label.as_bytes()]) {
+        LookupResult::Found(v) => Ok(v),
+        _ => Err(CertificationError::MissingLabel(label)),
+    }
+}
+
+impl<const A: bool> Canisters<A> {
+    /// Fetch the ledger's tip and verify its certificate against the agent's root key
+    pub async fn certified_ledger_tip(&self, ledger_id: Principal) -> Result<CertifiedTip> {
+        let agent = self.agent.get_agent().await;
+        let ledger = self.sns_ledger(ledger_id).await;
+        let tip = ledger
+            .icrc_3_get_tip_certificate()
+            .await?
+            .ok_or(Error::TipCertificate)?;
+
+        let certificate: Certificate = ciborium::from_reader(Cursor::new(&tip.certificate))?;
+        agent
+            .verify(&certificate, ledger_id)
+            .map_err(|e| CertificationError::Certificate(e.to_string()))?;
+
+        let certified_data_path = [
+            b"canister".as_slice(),
+            ledger_id.as_slice(),
+            b"certified_data".as_slice(),
+        ];
+        let LookupResult::Found(certified_data) = certificate.tree.lookup_path(certified_data_path)
+        else {
+            return Err(CertificationError::MissingLabel("certified_data").into());
+        };
+
+        let hash_tree: HashTree<Vec<u8>> = ciborium::from_reader(Cursor::new(&tip.hash_tree))?;
+        if hash_tree.digest().as_slice() != certified_data {
+            return Err(CertificationError::CertifiedDataMismatch.into());
+        }
+
+        let last_block_index = lookup_label(&hash_tree, "last_block_index")?
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| Error::TipCertificate)?;
+        let last_block_hash = lookup_label(&hash_tree, "last_block_hash")?
+            .try_into()
+            .map_err(|_| Error::TipCertificate)?;
+
+        Ok(CertifiedTip {
+            last_block_index,
+            last_block_hash,
+        })
+    }
+
+    /// Fetch blocks `start..=tip` from the ledger and verify that they hash-chain to the certified tip
+    ///
+    /// returns the verified blocks, newest first
+    /// fails with [`CertificationError::Archived`] if any of the blocks has been moved to an archive
+    pub async fn verified_ledger_blocks(
+        &self,
+        ledger_id: Principal,
+        start: u64,
+    ) -> Result<Vec<VerifiedBlock>> {
+        let tip = self.certified_ledger_tip(ledger_id).await?;
+        if start > tip.last_block_index {
+            return Ok(vec![]);
+        }
+
+        let ledger = self.sns_ledger(ledger_id).await;
+        let mut verified = Vec::new();
+        let mut expected_hash = Some(tip.last_block_hash);
+        let mut page_end = tip.last_block_index + 1;
+        while page_end > start {
+            let page_start = page_end.saturating_sub(BLOCKS_PAGE_SIZE).max(start);
+            let res = ledger
+                .icrc_3_get_blocks(vec![GetBlocksArgs {
+                    start: page_start.into(),
+                    length: (page_end - page_start).into(),
+                }])
+                .await?;
+            if !res.archived_blocks.is_empty() {
+                return Err(CertificationError::Archived(page_start).into());
+            }
+
+            let mut blocks = res.blocks;
+            blocks.sort_by(|a, b| b.id.cmp(&a.id));
+            let mut index = page_end;
+            for b in blocks {
+                index -= 1;
+                if b.id != Nat::from(index) {
+                    return Err(CertificationError::MissingBlock(index).into());
+                }
+
+                let hash = icrc3_value_hash(&b.block);
+                if expected_hash != Some(hash) {
+                    return Err(CertificationError::HashMismatch(index).into());
+                }
+                expected_hash = parent_hash(&b.block)?;
+                verified.push(VerifiedBlock {
+                    index,
+                    hash,
+                    block: b.block,
+                });
+            }
+            if index != page_start {
+                return Err(CertificationError::MissingBlock(index - 1).into());
+            }
+            page_end = page_start;
+        }
+
+        Ok(verified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(hex: &str) -> [u8; 32] {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        bytes.try_into().unwrap()
+    }
+
+    fn nat(n: u64) -> Icrc3Value {
+        Icrc3Value::Nat(Nat::from(n))
+    }
+
+    fn blob(bytes: &[u8]) -> Icrc3Value {
+        Icrc3Value::Blob(bytes.to_vec().into())
+    }
+
+    // test vectors from the ICRC-3 standard
+
+    #[test]
+    fn hashes_scalars() {
+        assert_eq!(
+            icrc3_value_hash(&nat(42)),
+            hash("684888c0ebb17f374298b65ee2807526c066094c701bcc7ebbe1c1095f494fc1")
+        );
+        assert_eq!(
+            icrc3_value_hash(&Icrc3Value::Int(Int::from(-42))),
+            hash("de5a6f78116eca62d7fc5ce159d23ae6b889b365a1739ad2cf36f925a140d0cc")
+        );
+        assert_eq!(
+            icrc3_value_hash(&Icrc3Value::Text("Hello, World!".into())),
+            hash("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f")
+        );
+        assert_eq!(
+            icrc3_value_hash(&blob(&[1, 2, 3, 4])),
+            hash("9f64a747e1b97f131fabb6b447296c9b6f0201e79fb3c5356e6c77e89b6a806a")
+        );
+    }
+
+    #[test]
+    fn hashes_arrays() {
+        let value = Icrc3Value::Array(vec![nat(3), Icrc3Value::Text("foo".into()), blob(&[5, 6])]);
+        assert_eq!(
+            icrc3_value_hash(&value),
+            hash("514a04011caa503990d446b7dec5d79e19c221ae607fb08b2848c67734d468d6")
+        );
+    }
+
+    #[test]
+    fn hashes_maps_independent_of_order() {
+        let mut entries = vec![
+            (
+                "from".to_string(),
+                blob(&[
+                    0x00, 0xab, 0xcd, 0xef, 0x00, 0x12, 0x34, 0x00, 0x56, 0x78, 0x9a, 0x00, 0xbc,
+                    0xde, 0xf0, 0x00, 0x01, 0x23, 0x45, 0x67, 0x89, 0x00, 0xab, 0xcd, 0xef, 0x01,
+                ]),
+            ),
+            (
+                "to".to_string(),
+                blob(&[
+                    0x00, 0xab, 0x0d, 0xef, 0x00, 0x12, 0x34, 0x00, 0x56, 0x78, 0x9a, 0x00, 0xbc,
+                    0xde, 0xf0, 0x00, 0x01, 0x23, 0x45, 0x67, 0x89, 0x00, 0xab, 0xcd, 0xef, 0x01,
+                ]),
+            ),
+            ("amount".to_string(), nat(42)),
+            ("created_at".to_string(), nat(1699218263)),
+            ("memo".to_string(), nat(0)),
+        ];
+        let expected = hash("c56ece650e1de4269c5bdeff7875949e3e2033f85b2d193c2ff4f7f78bdcfc75");
+        assert_eq!(
+            icrc3_value_hash(&Icrc3Value::Map(entries.clone())),
+            expected
+        );
+
+        entries.reverse();
+        assert_eq!(icrc3_value_hash(&Icrc3Value::Map(entries)), expected);
+    }
+
+    #[test]
+    fn leb128_nat_edge_cases() {
+        let cases: [(u64, &[u8]); 4] = [
+            (0, &[0x00]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (624485, &[0xe5, 0x8e, 0x26]),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(leb128_nat(&BigUint::from(n)), expected, "{n}");
+        }
+    }
+
+    #[test]
+    fn leb128_int_edge_cases() {
+        let cases: [(i64, &[u8]); 7] = [
+            (0, &[0x00]),
+            (-1, &[0x7f]),
+            (63, &[0x3f]),
+            (64, &[0xc0, 0x00]),
+            (-64, &[0x40]),
+            (-65, &[0xbf, 0x7f]),
+            (-123456, &[0xc0, 0xbb, 0x78]),
+        ];
+        for (i, expected) in cases {
+            assert_eq!(leb128_int(&BigInt::from(i)), expected, "{i}");
+        }
+    }
+}
@@ -1,6 +1,13 @@
+pub mod btc;
+pub mod icrc3;
+pub mod neuron;
 pub mod posts;
 pub mod profile;
+pub mod proposal;
+pub mod referral;
+pub mod swap;
 pub mod time;
 pub mod token;
 pub mod transaction;
+pub mod txn_watcher;
 pub mod vote;
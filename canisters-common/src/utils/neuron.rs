@@ -0,0 +1,189 @@
+use candid::Principal;
+use canisters_client::{
+    sns_governance::{
+        Account as GovernanceAccount, Amount, By, ClaimOrRefresh, Command, Command1, Configure,
+        Disburse, IncreaseDissolveDelay, ManageNeuron, MemoAndController, NeuronId, Operation,
+    },
+    sns_ledger::{self, Account as LedgerAccount},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{Canisters, Result, SnsGovernanceError};
+
+use super::token::balance::TokenBalance;
+
+/// Subaccount of the governance canister that backs the neuron staked by `controller` with `memo`
+/// also serves as the id of the neuron
+pub fn neuron_staking_subaccount(controller: Principal, memo: u64) -> [u8; 32] {
+    const DOMAIN: &[u8] = b"neuron-stake";
+
+    let mut hasher = Sha256::new();
+    hasher.update([DOMAIN.len() as u8]);
+    hasher.update(DOMAIN);
+    hasher.update(controller.as_slice());
+    hasher.update(memo.to_be_bytes());
+    hasher.finalize().into()
+}
+
+impl Canisters<true> {
+    pub(crate) async fn manage_neuron(
+        &self,
+        governance: Principal,
+        neuron_id: &NeuronId,
+        command: Command,
+    ) -> Result<Command1> {
+        let governance = self.sns_governance(governance).await;
+        let res = governance
+            .manage_neuron(ManageNeuron {
+                subaccount: neuron_id.id.clone(),
+                command: Some(command),
+            })
+            .await?;
+
+        match res.command {
+            Some(Command1::Error(e)) => Err(SnsGovernanceError::from(e).into()),
+            Some(command) => Ok(command),
+            None => Err(SnsGovernanceError::UnexpectedResponse.into()),
+        }
+    }
+
+    async fn configure_neuron(
+        &self,
+        governance: Principal,
+        neuron_id: &NeuronId,
+        operation: Operation,
+    ) -> Result<()> {
+        let res = self
+            .manage_neuron(
+                governance,
+                neuron_id,
+                Command::Configure(Configure {
+                    operation: Some(operation),
+                }),
+            )
+            .await?;
+
+        match res {
+            Command1::Configure {} => Ok(()),
+            _ => Err(SnsGovernanceError::UnexpectedResponse.into()),
+        }
+    }
+
+    /// Stake `amount` of the SNS token in a new neuron controlled by the user
+    /// `memo` must be unique among the user's neurons in this SNS
+    /// the ledger fee is charged on top of `amount`
+    pub async fn stake_sns_neuron(
+        &self,
+        governance: Principal,
+        ledger: Principal,
+        amount: TokenBalance,
+        memo: u64,
+    ) -> Result<NeuronId> {
+        let controller = self.user_principal();
+        let subaccount = neuron_staking_subaccount(controller, memo);
+
+        let ledger = self.sns_ledger(ledger).await;
+        let transfer_res = ledger
+            .icrc_1_transfer(sns_ledger::TransferArg {
+                memo: Some(memo.to_be_bytes().to_vec().into()),
+                amount: amount.into(),
+                fee: None,
+                from_subaccount: None,
+                to: LedgerAccount {
+                    owner: governance,
+                    subaccount: Some(subaccount.to_vec().into()),
+                },
+                created_at_time: None,
+            })
+            .await?;
+        if let sns_ledger::TransferResult::Err(e) = transfer_res {
+            return Err(SnsGovernanceError::StakeTransfer(format!("{e:?}")).into());
+        }
+
+        let neuron_id = NeuronId {
+            id: subaccount.to_vec().into(),
+        };
+        let res = self
+            .manage_neuron(
+                governance,
+                &neuron_id,
+                Command::ClaimOrRefresh(ClaimOrRefresh {
+                    by: Some(By::MemoAndController(MemoAndController {
+                        controller: Some(controller),
+                        memo,
+                    })),
+                }),
+            )
+            .await?;
+
+        match res {
+            Command1::ClaimOrRefresh(res) => Ok(res.refreshed_neuron_id.unwrap_or(neuron_id)),
+            _ => Err(SnsGovernanceError::UnexpectedResponse.into()),
+        }
+    }
+
+    /// Increase the dissolve delay of the neuron by `additional_secs`
+    pub async fn increase_neuron_dissolve_delay(
+        &self,
+        governance: Principal,
+        neuron_id: &NeuronId,
+        additional_secs: u32,
+    ) -> Result<()> {
+        self.configure_neuron(
+            governance,
+            neuron_id,
+            Operation::IncreaseDissolveDelay(IncreaseDissolveDelay {
+                additional_dissolve_delay_seconds: additional_secs,
+            }),
+        )
+        .await
+    }
+
+    pub async fn start_dissolving_neuron(
+        &self,
+        governance: Principal,
+        neuron_id: &NeuronId,
+    ) -> Result<()> {
+        self.configure_neuron(governance, neuron_id, Operation::StartDissolving {})
+            .await
+    }
+
+    pub async fn stop_dissolving_neuron(
+        &self,
+        governance: Principal,
+        neuron_id: &NeuronId,
+    ) -> Result<()> {
+        self.configure_neuron(governance, neuron_id, Operation::StopDissolving {})
+            .await
+    }
+
+    /// Disburse a dissolved neuron's stake to `to` (default: the user's main account)
+    /// disburses the entire stake if `amount_e8s` is `None`
+    /// returns the block height of the ledger transfer
+    pub async fn disburse_neuron(
+        &self,
+        governance: Principal,
+        neuron_id: &NeuronId,
+        amount_e8s: Option<u64>,
+        to: Option<Principal>,
+    ) -> Result<u64> {
+        let res = self
+            .manage_neuron(
+                governance,
+                neuron_id,
+                Command::Disburse(Disburse {
+                    to_account: Some(GovernanceAccount {
+                        owner: Some(to.unwrap_or_else(|| self.user_principal())),
+                        subaccount: None,
+                    }),
+                    amount: amount_e8s.map(|e8s| Amount { e8s }),
+                }),
+            )
+            .await?;
+
+        match res {
+            Command1::Disburse(res) => Ok(res.transfer_block_height),
+            _ => Err(SnsGovernanceError::UnexpectedResponse.into()),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use candid::Principal;
+use canisters_client::sns_governance::{
+    Command, Command1, NeuronId, Percentage, ProposalData, ProposalId, RegisterVote, Tally,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Canisters, Result, SnsGovernanceError};
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum ProposalVote {
+    Yes,
+    No,
+}
+
+impl ProposalVote {
+    /// Representation used by SNS governance
+    pub fn as_i32(self) -> i32 {
+        match self {
+            Self::Yes => 1,
+            Self::No => 2,
+        }
+    }
+
+    pub fn from_i32(vote: i32) -> Option<Self> {
+        match vote {
+            1 => Some(Self::Yes),
+            2 => Some(Self::No),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Open,
+    Rejected,
+    Adopted,
+    Executed,
+    Failed,
+}
+
+impl ProposalStatus {
+    /// `ProposalDecisionStatus` representation used by SNS governance
+    pub fn as_i32(self) -> i32 {
+        match self {
+            Self::Open => 1,
+            Self::Rejected => 2,
+            Self::Adopted => 3,
+            Self::Executed => 4,
+            Self::Failed => 5,
+        }
+    }
+
+    /// Only adopted proposals get an execution or failure timestamp, the
+    /// tally is consulted only for decided proposals still awaiting execution
+    fn from_proposal_data(data: &ProposalData) -> Self {
+        if data.executed_timestamp_seconds > 0 {
+            return Self::Executed;
+        }
+        if data.failed_timestamp_seconds > 0 {
+            return Self::Failed;
+        }
+        if data.decided_timestamp_seconds == 0 {
+            return Self::Open;
+        }
+
+        let accepted = data.latest_tally.as_ref().is_some_and(|tally| {
+            is_accepted(
+                tally,
+                data.minimum_yes_proportion_of_exercised.as_ref(),
+                data.minimum_yes_proportion_of_total.as_ref(),
+            )
+        });
+        if accepted {
+            Self::Adopted
+        } else {
+            Self::Rejected
+        }
+    }
+}
+
+/// SNS governance defaults, used when the proposal doesn't carry its own thresholds
+const DEFAULT_MIN_YES_OF_EXERCISED_BP: u64 = 5_000;
+const DEFAULT_MIN_YES_OF_TOTAL_BP: u64 = 300;
+
+/// Mirrors SNS governance's acceptance rule for a decided tally
+fn is_accepted(
+    tally: &Tally,
+    min_yes_of_exercised: Option<&Percentage>,
+    min_yes_of_total: Option<&Percentage>,
+) -> bool {
+    let basis_points = |p: Option<&Percentage>, default: u64| {
+        p.and_then(|p| p.basis_points).unwrap_or(default) as u128
+    };
+    let yes = tally.yes as u128;
+    let exercised = yes + tally.no as u128;
+
+    yes * 10_000 > exercised * basis_points(min_yes_of_exercised, DEFAULT_MIN_YES_OF_EXERCISED_BP)
+        && yes * 10_000
+            >= tally.total as u128 * basis_points(min_yes_of_total, DEFAULT_MIN_YES_OF_TOTAL_BP)
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProposalBallot {
+    /// hex encoded neuron id
+    pub neuron_id: String,
+    /// `None` if the neuron hasn't voted yet
+    pub vote: Option<ProposalVote>,
+    pub voting_power: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProposalDetails {
+    pub id: u64,
+    pub title: String,
+    pub summary: String,
+    pub url: String,
+    pub status: ProposalStatus,
+    pub yes_e8s: u64,
+    pub no_e8s: u64,
+    pub total_e8s: u64,
+    pub created_at_secs: u64,
+    /// Voting closes at this time, unless the proposal is decided earlier
+    pub deadline_secs: u64,
+    /// Ballots of the caller's neurons
+    pub ballots: Vec<ProposalBallot>,
+}
+
+impl ProposalDetails {
+    /// returns `None` for proposals without an id
+    pub fn from_proposal_data(data: ProposalData) -> Option<Self> {
+        let id = data.id.as_ref()?.id;
+        let (yes_e8s, no_e8s, total_e8s) = data
+            .latest_tally
+            .as_ref()
+            .map(|t| (t.yes, t.no, t.total))
+            .unwrap_or_default();
+
+        let status = ProposalStatus::from_proposal_data(&data);
+
+        let deadline_secs = data
+            .wait_for_quiet_state
+            .as_ref()
+            .map(|w| w.current_deadline_timestamp_seconds)
+            .unwrap_or(
+                data.proposal_creation_timestamp_seconds + data.initial_voting_period_seconds,
+            );
+
+        let ballots = data
+            .ballots
+            .into_iter()
+            .map(|(neuron_id, ballot)| ProposalBallot {
+                neuron_id,
+                vote: ProposalVote::from_i32(ballot.vote),
+                voting_power: ballot.voting_power,
+            })
+            .collect();
+
+        let (title, summary, url) = data
+            .proposal
+            .map(|p| (p.title, p.summary, p.url))
+            .unwrap_or_default();
+
+        Some(Self {
+            id,
+            title,
+            summary,
+            url,
+            status,
+            yes_e8s,
+            no_e8s,
+            total_e8s,
+            created_at_secs: data.proposal_creation_timestamp_seconds,
+            deadline_secs,
+            ballots,
+        })
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status == ProposalStatus::Open
+    }
+}
+
+impl Canisters<true> {
+    /// Vote on an SNS proposal with one of the user's neurons
+    pub async fn vote_on_proposal(
+        &self,
+        governance: Principal,
+        neuron_id: &NeuronId,
+        proposal_id: u64,
+        vote: ProposalVote,
+    ) -> Result<()> {
+        let res = self
+            .manage_neuron(
+                governance,
+                neuron_id,
+                Command::RegisterVote(RegisterVote {
+                    vote: vote.as_i32(),
+                    proposal: Some(ProposalId { id: proposal_id }),
+                }),
+            )
+            .await?;
+
+        match res {
+            Command1::RegisterVote {} => Ok(()),
+            _ => Err(SnsGovernanceError::UnexpectedResponse.into()),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use candid::Principal;
+use hon_worker_common::{
+    default_referral_amount, sign_referral_request_with_nonce, HonWorkerClient, ReferralReq,
+    ReferralReqWithSignature,
+};
+
+use crate::{Canisters, HonError, Result};
+
+/// Random nonce so the signed request can only be submitted once
+fn referral_nonce() -> Result<u64> {
+    let mut nonce = [0u8; 8];
+    getrandom::getrandom(&mut nonce)?;
+    Ok(u64::from_le_bytes(nonce))
+}
+
+impl Canisters<true> {
+    /// Sign a claim for the referral reward of signing up with `referrer`'s referral
+    ///
+    /// see [`Self::submit_referral`] to also submit it
+    pub fn sign_referral(&self, referrer: Principal) -> Result<ReferralReqWithSignature> {
+        let request = ReferralReq {
+            referrer,
+            referee: self.user_principal(),
+            referee_canister: self.user_canister(),
+            amount: default_referral_amount(),
+        };
+        let nonce = referral_nonce()?;
+        let signature = sign_referral_request_with_nonce(self.identity(), request.clone(), nonce)?;
+
+        Ok(ReferralReqWithSignature {
+            request,
+            signature,
+            nonce: Some(nonce),
+        })
+    }
+
+    /// Claim the referral reward for signing up with `referrer`'s referral
+    pub async fn submit_referral(&self, referrer: Principal) -> Result<()> {
+        let req = self.sign_referral(referrer)?;
+        HonWorkerClient::default()
+            .referral(&req)
+            .await
+            .map_err(HonError::from)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+use candid::Principal;
+use canisters_client::{
+    sns_ledger::{self, Account as LedgerAccount},
+    sns_root::ListSnsCanistersArg,
+    sns_swap::{
+        BuyerState, GetBuyerStateRequest, GetLifecycleArg, GetSaleParametersArg,
+        RefreshBuyerTokensRequest,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    consts::ICP_LEDGER_CANISTER, utils::time::current_epoch, Canisters, Result, SwapError,
+};
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum SwapLifecycle {
+    Unspecified,
+    Pending,
+    Open,
+    Committed,
+    Aborted,
+    Adopted,
+}
+
+impl SwapLifecycle {
+    pub fn from_i32(lifecycle: i32) -> Self {
+        match lifecycle {
+            1 => Self::Pending,
+            2 => Self::Open,
+            3 => Self::Committed,
+            4 => Self::Aborted,
+            5 => Self::Adopted,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SwapStatus {
+    pub swap: Principal,
+    pub lifecycle: SwapLifecycle,
+    pub open_at_secs: Option<u64>,
+    pub closes_at_secs: Option<u64>,
+    pub min_participant_icp_e8s: u64,
+    pub max_participant_icp_e8s: u64,
+}
+
+impl SwapStatus {
+    /// Whether the swap accepts participation at `now_secs`
+    pub fn is_open_at(&self, now_secs: u64) -> bool {
+        self.lifecycle == SwapLifecycle::Open
+            && self.open_at_secs.is_none_or(|open| open <= now_secs)
+            && self.closes_at_secs.is_none_or(|close| now_secs < close)
+    }
+
+    /// Mirrors the swap's own checks, which apply to the buyer's total participation
+    fn check_participation(
+        &self,
+        accepted_icp_e8s: u64,
+        amount_icp_e8s: u64,
+    ) -> Result<(), SwapError> {
+        if !self.is_open_at(current_epoch().as_secs()) {
+            return Err(SwapError::NotOpen(self.lifecycle));
+        }
+        let total_icp_e8s = accepted_icp_e8s.saturating_add(amount_icp_e8s);
+        if total_icp_e8s < self.min_participant_icp_e8s {
+            return Err(SwapError::InvalidAmount {
+                min: self.min_participant_icp_e8s,
+                max: self.max_participant_icp_e8s,
+            });
+        }
+        if total_icp_e8s > self.max_participant_icp_e8s {
+            return Err(SwapError::MaxParticipationExceeded {
+                accepted: accepted_icp_e8s,
+                max: self.max_participant_icp_e8s,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+pub struct SwapParticipation {
+    /// ICP accepted by the swap for the buyer, across all their participations
+    pub accepted_icp_e8s: u64,
+    /// ICP held by the swap in the buyer's subaccount
+    pub account_balance_icp_e8s: u64,
+}
+
+/// Subaccount of the swap canister where `buyer` sends their ICP
+pub fn swap_buyer_subaccount(buyer: Principal) -> [u8; 32] {
+    let mut subaccount = [0u8; 32];
+    let bytes = buyer.as_slice();
+    subaccount[0] = bytes.len() as u8;
+    subaccount[1..1 + bytes.len()].copy_from_slice(bytes);
+    subaccount
+}
+
+impl<const A: bool> Canisters<A> {
+    pub async fn swap_canister_by_root(&self, token_root: Principal) -> Result<Principal> {
+        let root = self.sns_root(token_root).await;
+        let sns_cans = root.list_sns_canisters(ListSnsCanistersArg {}).await?;
+        sns_cans
+            .swap
+            .ok_or_else(|| SwapError::SwapNotFound(token_root).into())
+    }
+
+    /// Lifecycle and participation limits of the SNS swap for `token_root`
+    pub async fn swap_status(&self, token_root: Principal) -> Result<SwapStatus> {
+        let swap_id = self.swap_canister_by_root(token_root).await?;
+        let swap = self.sns_swap(swap_id).await;
+
+        let lifecycle = swap.get_lifecycle(GetLifecycleArg {}).await?;
+        let params = swap
+            .get_sale_parameters(GetSaleParametersArg {})
+            .await?
+            .params
+            .ok_or(SwapError::MissingParams)?;
+
+        Ok(SwapStatus {
+            swap: swap_id,
+            lifecycle: SwapLifecycle::from_i32(lifecycle.lifecycle.unwrap_or_default()),
+            open_at_secs: lifecycle.decentralization_sale_open_timestamp_seconds,
+            closes_at_secs: lifecycle.decentralization_swap_termination_timestamp_seconds,
+            min_participant_icp_e8s: params.min_participant_icp_e8s,
+            max_participant_icp_e8s: params.max_participant_icp_e8s,
+        })
+    }
+
+    pub async fn swap_buyer_state(
+        &self,
+        token_root: Principal,
+        buyer: Principal,
+    ) -> Result<Option<BuyerState>> {
+        let swap_id = self.swap_canister_by_root(token_root).await?;
+        self.buyer_state(swap_id, buyer).await
+    }
+
+    async fn buyer_state(
+        &self,
+        swap_id: Principal,
+        buyer: Principal,
+    ) -> Result<Option<BuyerState>> {
+        let swap = self.sns_swap(swap_id).await;
+        let res = swap
+            .get_buyer_state(GetBuyerStateRequest {
+                principal_id: Some(buyer),
+            })
+            .await?;
+
+        Ok(res.buyer_state)
+    }
+
+    /// Ask the swap to account for ICP sent to `buyer`'s subaccount
+    /// can be called on behalf of any buyer, e.g. by a `ParticipateInSwapRequest` job
+    pub async fn refresh_swap_participation(
+        &self,
+        token_root: Principal,
+        buyer: Principal,
+    ) -> Result<SwapParticipation> {
+        let swap_id = self.swap_canister_by_root(token_root).await?;
+        self.refresh_buyer_tokens(swap_id, buyer).await
+    }
+
+    async fn refresh_buyer_tokens(
+        &self,
+        swap_id: Principal,
+        buyer: Principal,
+    ) -> Result<SwapParticipation> {
+        let swap = self.sns_swap(swap_id).await;
+        let res = swap
+            .refresh_buyer_tokens(RefreshBuyerTokensRequest {
+                confirmation_text: None,
+                buyer: buyer.to_text(),
+            })
+            .await?;
+
+        Ok(SwapParticipation {
+            accepted_icp_e8s: res.icp_accepted_participation_e8s,
+            account_balance_icp_e8s: res.icp_ledger_account_balance_e8s,
+        })
+    }
+}
+
+impl Canisters<true> {
+    /// Participate in the SNS swap for `token_root` with `amount_icp_e8s` of the user's ICP
+    /// fails without transferring anything if the swap isn't open or the user's
+    /// total participation would be out of bounds
+    pub async fn participate_in_swap(
+        &self,
+        token_root: Principal,
+        amount_icp_e8s: u64,
+    ) -> Result<SwapParticipation> {
+        let status = self.swap_status(token_root).await?;
+        let buyer = self.user_principal();
+        let accepted_icp_e8s = self
+            .buyer_state(status.swap, buyer)
+            .await?
+            .and_then(|state| state.icp)
+            .map(|icp| icp.amount_e8s)
+            .unwrap_or_default();
+        status.check_participation(accepted_icp_e8s, amount_icp_e8s)?;
+
+        let icp_ledger = Principal::from_text(ICP_LEDGER_CANISTER).unwrap();
+        let ledger = self.sns_ledger(icp_ledger).await;
+        let res = ledger
+            .icrc_1_transfer(sns_ledger::TransferArg {
+                memo: None,
+                amount: amount_icp_e8s.into(),
+                fee: None,
+                from_subaccount: None,
+                to: LedgerAccount {
+                    owner: status.swap,
+                    subaccount: Some(swap_buyer_subaccount(buyer).to_vec().into()),
+                },
+                created_at_time: None,
+            })
+            .await?;
+        if let sns_ledger::TransferResult::Err(e) = res {
+            return Err(SwapError::Transfer(format!("{e:?}")).into());
+        }
+
+        self.refresh_buyer_tokens(status.swap, buyer).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_swap() -> SwapStatus {
+        SwapStatus {
+            swap: Principal::anonymous(),
+            lifecycle: SwapLifecycle::Open,
+            open_at_secs: None,
+            closes_at_secs: None,
+            min_participant_icp_e8s: 100,
+            max_participant_icp_e8s: 1_000,
+        }
+    }
+
+    #[test]
+    fn participation_counts_accepted_icp() {
+        let swap = open_swap();
+        assert!(swap.check_participation(0, 1_000).is_ok());
+        assert!(swap.check_participation(900, 100).is_ok());
+        assert!(matches!(
+            swap.check_participation(900, 101),
+            Err(SwapError::MaxParticipationExceeded {
+                accepted: 900,
+                max: 1_000
+            })
+        ));
+        // top ups below the minimum are fine once the total clears it
+        assert!(swap.check_participation(200, 1).is_ok());
+        assert!(matches!(
+            swap.check_participation(0, 99),
+            Err(SwapError::InvalidAmount { .. })
+        ));
+    }
+
+    #[test]
+    fn participation_requires_open_swap() {
+        let swap = SwapStatus {
+            lifecycle: SwapLifecycle::Committed,
+            ..open_swap()
+        };
+        assert!(matches!(
+            swap.check_participation(0, 500),
+            Err(SwapError::NotOpen(SwapLifecycle::Committed))
+        ));
+    }
+}
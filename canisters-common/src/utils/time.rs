@@ -5,3 +5,15 @@ pub fn current_epoch() -> Duration {
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap()
 }
+
+/// Wait for `duration`, using browser timers with the `js` feature
+pub async fn sleep(duration: Duration) {
+    #[cfg(feature = "js")]
+    {
+        gloo_timers::future::sleep(duration).await
+    }
+    #[cfg(not(feature = "js"))]
+    {
+        tokio::time::sleep(duration).await
+    }
+}
@@ -5,9 +5,51 @@ use std::{
 };
 
 use candid::Nat;
+use num_bigint::BigUint;
 use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
+use super::ClaimStatus;
+use crate::TokenBalanceError;
+
+/// Formatting options for [`TokenBalance::format`]
+#[derive(Clone, Debug, Default)]
+pub struct BalanceFormat {
+    /// Use K/M/B/T suffixes for large amounts
+    pub compact: bool,
+    /// Number of decimal places, truncated and zero padded
+    /// defaults to all significant digits (1 for compact amounts)
+    pub precision: Option<u32>,
+    /// Appended after the amount, e.g. "SATS"
+    pub symbol: Option<String>,
+}
+
+impl BalanceFormat {
+    pub fn compact(mut self) -> Self {
+        self.compact = true;
+        self
+    }
+
+    pub fn precision(mut self, dp: u32) -> Self {
+        self.precision = Some(dp);
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+}
+
+/// Largest scale supported by [`Decimal`]
+const MAX_DECIMAL_SCALE: u8 = 28;
+
+const COMPACT_SUFFIXES: [(u32, &str); 4] = [(12, "T"), (9, "B"), (6, "M"), (3, "K")];
+
+fn pow10(exp: u32) -> BigUint {
+    BigUint::from(10u8).pow(exp)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TokenBalance {
     pub e8s: Nat,
@@ -26,9 +68,15 @@ impl TokenBalance {
         Ok(Self::new(e8s, decimals))
     }
 
-    // Human friendly token amount
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    // Human friendly token amount, rounded to whole tokens
     pub fn humanize(&self) -> String {
-        (self.e8s.clone() / 10u64.pow(self.decimals as u32))
+        let unit = pow10(self.decimals as u32);
+        let half = &unit / 2u8;
+        Nat((&self.e8s.0 + half) / unit)
             .to_string()
             .replace("_", ",")
     }
@@ -49,6 +97,129 @@ impl TokenBalance {
             .to_string()
     }
 
+    // Value of the balance in USD, given the USD price of one whole token
+    // None if the value doesn't fit in a Decimal
+    pub fn usd_value(&self, usd_price: Decimal) -> Option<Decimal> {
+        self.to_decimal()?.checked_mul(usd_price)
+    }
+
+    /// Amount in whole tokens
+    /// returns `None` if the amount doesn't fit in a [`Decimal`]
+    /// i.e. more than 28 decimals or 96 bits of e8s
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        let mut tokens = Decimal::from_str(&self.e8s.0.to_str_radix(10)).ok()?;
+        tokens.set_scale(self.decimals as u32).ok()?;
+        Some(tokens)
+    }
+
+    /// [`Self::to_decimal`], dropping the decimals that don't fit
+    fn to_decimal_truncating(&self) -> Option<Decimal> {
+        (0..=self.decimals.min(MAX_DECIMAL_SCALE))
+            .rev()
+            .find_map(|decimals| self.rescale_truncating(decimals).to_decimal())
+    }
+
+    fn ensure_same_decimals(&self, other: &Self) -> Result<(), TokenBalanceError> {
+        if self.decimals != other.decimals {
+            return Err(TokenBalanceError::DecimalsMismatch {
+                left: self.decimals,
+                right: other.decimals,
+            });
+        }
+        Ok(())
+    }
+
+    /// Add two balances, failing if they have different decimals
+    pub fn checked_add(&self, other: &Self) -> Result<Self, TokenBalanceError> {
+        self.ensure_same_decimals(other)?;
+        Ok(Self::new(
+            self.e8s.clone() + other.e8s.clone(),
+            self.decimals,
+        ))
+    }
+
+    /// Subtract `other` from this balance, failing if they have different decimals
+    /// or if `other` is larger
+    pub fn checked_sub(&self, other: &Self) -> Result<Self, TokenBalanceError> {
+        self.ensure_same_decimals(other)?;
+        if other.e8s > self.e8s {
+            return Err(TokenBalanceError::Underflow);
+        }
+        Ok(Self::new(
+            self.e8s.clone() - other.e8s.clone(),
+            self.decimals,
+        ))
+    }
+
+    /// Represent the same amount with `decimals` decimals
+    /// fails if digits would be dropped
+    pub fn rescale(&self, decimals: u8) -> Result<Self, TokenBalanceError> {
+        if decimals >= self.decimals {
+            let factor = pow10((decimals - self.decimals) as u32);
+            return Ok(Self::new(Nat(self.e8s.0.clone() * factor), decimals));
+        }
+
+        let factor = pow10((self.decimals - decimals) as u32);
+        if (&self.e8s.0 % &factor).bits() != 0 {
+            return Err(TokenBalanceError::PrecisionLoss(decimals));
+        }
+        Ok(Self::new(Nat(self.e8s.0.clone() / factor), decimals))
+    }
+
+    /// Represent the amount with `decimals` decimals, dropping digits that don't fit
+    pub fn rescale_truncating(&self, decimals: u8) -> Self {
+        if decimals >= self.decimals {
+            return self.rescale(decimals).unwrap();
+        }
+        let factor = pow10((self.decimals - decimals) as u32);
+        Self::new(Nat(self.e8s.0.clone() / factor), decimals)
+    }
+
+    /// Format the amount according to `fmt`
+    ///
+    /// amounts too large for a [`Decimal`] are shown in whole tokens
+    pub fn format(&self, fmt: &BalanceFormat) -> String {
+        let Some(mut tokens) = self.to_decimal_truncating() else {
+            return match &fmt.symbol {
+                Some(symbol) => format!("{} {symbol}", self.to_tokens()),
+                None => self.to_tokens(),
+            };
+        };
+        let mut suffix = "";
+        if fmt.compact {
+            if let Some((exp, s)) = COMPACT_SUFFIXES
+                .iter()
+                .find(|(exp, _)| tokens >= Decimal::from(10u64.pow(*exp)))
+            {
+                tokens /= Decimal::from(10u64.pow(*exp));
+                suffix = s;
+            }
+        }
+
+        let precision = fmt.precision.or(fmt.compact.then_some(1));
+        let amount = match precision {
+            Some(dp) => {
+                let tokens = tokens.round_dp_with_strategy(dp, RoundingStrategy::ToZero);
+                format!("{tokens:.*}", dp as usize)
+            }
+            None => tokens.normalize().to_string(),
+        };
+        let amount = if fmt.compact && fmt.precision.is_none() {
+            // 1.0K reads worse than 1K
+            amount
+                .strip_suffix(".0")
+                .map(str::to_string)
+                .unwrap_or(amount)
+        } else {
+            amount
+        };
+
+        match &fmt.symbol {
+            Some(symbol) => format!("{amount}{suffix} {symbol}"),
+            None => format!("{amount}{suffix}"),
+        }
+    }
+
     // Returns number of tokens(not e8s)
     pub fn to_tokens(&self) -> String {
         let tokens = self.e8s.clone() / Nat::from(10u64.pow(self.decimals as u32));
@@ -85,6 +256,25 @@ where
     }
 }
 
+impl Add for TokenBalance {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        debug_assert_eq!(self.decimals, rhs.decimals, "use `checked_add`");
+        Self {
+            e8s: self.e8s + rhs.e8s,
+            decimals: self.decimals,
+        }
+    }
+}
+
+impl AddAssign<TokenBalance> for TokenBalance {
+    fn add_assign(&mut self, rhs: TokenBalance) {
+        debug_assert_eq!(self.decimals, rhs.decimals, "use `checked_add`");
+        self.e8s += rhs.e8s;
+    }
+}
+
 impl<T> PartialEq<T> for TokenBalance
 where
     Nat: PartialEq<T>,
@@ -130,6 +320,7 @@ impl Sub for TokenBalance {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self {
+        debug_assert_eq!(self.decimals, rhs.decimals, "use `checked_sub`");
         Self {
             e8s: self.e8s - rhs.e8s,
             decimals: self.decimals,
@@ -139,6 +330,7 @@ impl Sub for TokenBalance {
 
 impl SubAssign<TokenBalance> for TokenBalance {
     fn sub_assign(&mut self, rhs: TokenBalance) {
+        debug_assert_eq!(self.decimals, rhs.decimals, "use `checked_sub`");
         self.e8s -= rhs.e8s;
     }
 }
@@ -155,24 +347,77 @@ impl PartialOrd for TokenBalance {
     }
 }
 
+/// A loaded balance, or "Processing" while tokens are moved from the user's neurons
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
-pub struct TokenBalanceOrClaiming(Option<TokenBalance>);
+#[serde(from = "TokenBalanceOrClaimingRepr")]
+pub struct TokenBalanceOrClaiming {
+    balance: Option<TokenBalance>,
+    claim_status: Option<ClaimStatus>,
+}
+
+/// Also accepts the old newtype encoding, i.e. a bare `Option<TokenBalance>`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TokenBalanceOrClaimingRepr {
+    Legacy(Option<TokenBalance>),
+    Current {
+        balance: Option<TokenBalance>,
+        #[serde(default)]
+        claim_status: Option<ClaimStatus>,
+    },
+}
+
+impl From<TokenBalanceOrClaimingRepr> for TokenBalanceOrClaiming {
+    fn from(repr: TokenBalanceOrClaimingRepr) -> Self {
+        match repr {
+            TokenBalanceOrClaimingRepr::Legacy(balance) => Self {
+                balance,
+                claim_status: None,
+            },
+            TokenBalanceOrClaimingRepr::Current {
+                balance,
+                claim_status,
+            } => Self {
+                balance,
+                claim_status,
+            },
+        }
+    }
+}
 
 impl TokenBalanceOrClaiming {
     pub fn new(balance: TokenBalance) -> Self {
-        Self(Some(balance))
+        Self {
+            balance: Some(balance),
+            claim_status: None,
+        }
     }
 
     pub fn claiming() -> Self {
-        Self(None)
+        Self {
+            balance: None,
+            claim_status: None,
+        }
+    }
+
+    /// Attach the claim status of the user's neurons this was derived from
+    pub fn with_claim_status(mut self, claim_status: ClaimStatus) -> Self {
+        self.claim_status = Some(claim_status);
+        self
     }
 
     pub fn is_claiming(&self) -> bool {
-        self.0.is_none()
+        self.balance.is_none()
+    }
+
+    /// Claim status of the user's neurons, `None` if it wasn't checked
+    /// e.g. because the ledger balance was already non-zero
+    pub fn claim_status(&self) -> Option<&ClaimStatus> {
+        self.claim_status.as_ref()
     }
 
     pub fn humanize(&self) -> String {
-        self.0
+        self.balance
             .as_ref()
             .map(|b| b.humanize())
             .unwrap_or_else(|| "Processing".to_string())
@@ -189,10 +434,110 @@ impl TokenBalanceOrClaiming {
     }
 
     pub fn map_balance<T>(self, f: impl FnOnce(TokenBalance) -> T) -> Option<T> {
-        self.0.map(f)
+        self.balance.map(f)
     }
 
     pub fn map_balance_ref<T>(&self, f: impl FnOnce(&TokenBalance) -> T) -> Option<T> {
-        self.0.as_ref().map(f)
+        self.balance.as_ref().map(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bal(e8s: u64, decimals: u8) -> TokenBalance {
+        TokenBalance::new(Nat::from(e8s), decimals)
+    }
+
+    #[test]
+    fn checked_add_requires_same_decimals() {
+        assert_eq!(bal(150, 2).checked_add(&bal(25, 2)).unwrap(), bal(175, 2));
+        assert!(matches!(
+            bal(150, 2).checked_add(&bal(25, 8)),
+            Err(TokenBalanceError::DecimalsMismatch { left: 2, right: 8 })
+        ));
+    }
+
+    #[test]
+    fn checked_sub_rejects_underflow() {
+        assert_eq!(bal(150, 2).checked_sub(&bal(50, 2)).unwrap(), bal(100, 2));
+        assert!(matches!(
+            bal(50, 2).checked_sub(&bal(51, 2)),
+            Err(TokenBalanceError::Underflow)
+        ));
+        assert!(matches!(
+            bal(150, 2).checked_sub(&bal(50, 6)),
+            Err(TokenBalanceError::DecimalsMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rescale_keeps_the_amount() {
+        let rescaled = bal(1_500_000, 6).rescale(8).unwrap();
+        assert_eq!(rescaled.decimals(), 8);
+        assert_eq!(rescaled, Nat::from(150_000_000u64));
+
+        assert_eq!(bal(1_500_000, 6).rescale(2).unwrap(), Nat::from(150u64));
+        assert!(matches!(
+            bal(1_500_001, 6).rescale(2),
+            Err(TokenBalanceError::PrecisionLoss(2))
+        ));
+        assert_eq!(bal(1_500_001, 6).rescale_truncating(2), Nat::from(150u64));
+    }
+
+    #[test]
+    fn to_decimal_handles_out_of_range_amounts() {
+        assert_eq!(
+            bal(123_456, 3).to_decimal(),
+            Decimal::from_str("123.456").ok()
+        );
+        assert_eq!(bal(1, 29).to_decimal(), None);
+
+        let huge = TokenBalance::new(Nat(BigUint::from(10u8).pow(30)), 0);
+        assert_eq!(huge.to_decimal(), None);
+        assert_eq!(huge.format(&BalanceFormat::default()), huge.to_tokens());
+    }
+
+    #[test]
+    fn humanize_rounds_to_whole_tokens() {
+        assert_eq!(bal(149, 2).humanize(), "1");
+        assert_eq!(bal(150, 2).humanize(), "2");
+        assert_eq!(bal(123_456_789, 0).humanize(), "123,456,789");
+    }
+
+    #[test]
+    fn format() {
+        let fmt = BalanceFormat::default;
+        assert_eq!(bal(123_450, 4).format(&fmt()), "12.345");
+        assert_eq!(bal(123_456, 4).format(&fmt().precision(2)), "12.34");
+        assert_eq!(bal(120_000, 4).format(&fmt().precision(3)), "12.000");
+        assert_eq!(bal(1_234_567, 2).format(&fmt().compact()), "12.3K");
+        assert_eq!(bal(100_000_000, 2).format(&fmt().compact()), "1M");
+        assert_eq!(bal(999, 2).format(&fmt().compact()), "9.9");
+        assert_eq!(
+            bal(2_500_000_000, 0).format(&fmt().compact().symbol("SATS")),
+            "2.5B SATS"
+        );
+        assert_eq!(bal(1, 29).format(&fmt()), "0");
+    }
+
+    #[test]
+    fn balance_or_claiming_serde() {
+        let legacy = serde_json::to_string(&Some(bal(150, 2))).unwrap();
+        let decoded: TokenBalanceOrClaiming = serde_json::from_str(&legacy).unwrap();
+        assert_eq!(decoded, TokenBalanceOrClaiming::new(bal(150, 2)));
+        let decoded: TokenBalanceOrClaiming = serde_json::from_str("null").unwrap();
+        assert!(decoded.is_claiming());
+
+        let with_status =
+            TokenBalanceOrClaiming::new(bal(0, 8)).with_claim_status(ClaimStatus::Claiming {
+                dissolving_e8s: 100,
+                dissolved_at_secs: 1_000,
+            });
+        let encoded = serde_json::to_string(&with_status).unwrap();
+        let decoded: TokenBalanceOrClaiming = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded, with_status);
+        assert!(decoded.claim_status().is_some());
     }
 }
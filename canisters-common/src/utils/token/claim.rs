@@ -0,0 +1,232 @@
+use candid::Principal;
+use canisters_client::{
+    sns_governance::{DissolveState, ListNeurons, Neuron},
+    sns_root::ListSnsCanistersArg,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{utils::time::current_epoch, Canisters, Error, Result};
+
+const NEURON_PAGE_SIZE: u32 = 50;
+
+/// State of a user's SNS tokens that are still held in neurons after the swap
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ClaimStatus {
+    /// The user doesn't have any neurons in this SNS
+    NoNeurons,
+    /// Neurons hold stake that can be disbursed to the user's ledger account right away
+    Unclaimed { claimable_e8s: u64 },
+    /// Neurons are dissolving, their stake becomes claimable once dissolved
+    Claiming {
+        dissolving_e8s: u64,
+        /// When the last dissolving neuron is fully dissolved
+        dissolved_at_secs: u64,
+    },
+    /// No neuron holds claimable or dissolving stake
+    Claimed,
+}
+
+impl ClaimStatus {
+    /// Derive the claim status from every neuron owned by the user
+    pub fn from_neurons(neurons: &[Neuron], now_secs: u64) -> Self {
+        if neurons.is_empty() {
+            return Self::NoNeurons;
+        }
+
+        let mut claimable_e8s = 0u64;
+        let mut dissolving_e8s = 0u64;
+        let mut dissolved_at_secs = 0u64;
+        for neuron in neurons {
+            let stake = neuron.cached_neuron_stake_e8s;
+            if stake == 0 {
+                continue;
+            }
+            match neuron.dissolve_state {
+                Some(DissolveState::DissolveDelaySeconds(0)) => {
+                    claimable_e8s = claimable_e8s.saturating_add(stake)
+                }
+                Some(DissolveState::WhenDissolvedTimestampSeconds(ts)) if ts <= now_secs => {
+                    claimable_e8s = claimable_e8s.saturating_add(stake)
+                }
+                Some(DissolveState::WhenDissolvedTimestampSeconds(ts)) => {
+                    dissolving_e8s = dissolving_e8s.saturating_add(stake);
+                    dissolved_at_secs = dissolved_at_secs.max(ts);
+                }
+                // locked stake that isn't dissolving is not part of the claim
+                Some(DissolveState::DissolveDelaySeconds(_)) | None => {}
+            }
+        }
+
+        if claimable_e8s > 0 {
+            Self::Unclaimed { claimable_e8s }
+        } else if dissolving_e8s > 0 {
+            Self::Claiming {
+                dissolving_e8s,
+                dissolved_at_secs,
+            }
+        } else {
+            Self::Claimed
+        }
+    }
+
+    /// Whether a claim job should be run for this user
+    pub fn needs_claim(&self) -> bool {
+        matches!(self, Self::Unclaimed { .. })
+    }
+
+    /// Whether some of the user's tokens are not in their ledger account yet
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Unclaimed { .. } | Self::Claiming { .. })
+    }
+}
+
+impl<const A: bool> Canisters<A> {
+    /// List every neuron of `user_principal` in the given SNS governance, across all pages
+    pub async fn list_all_neurons(
+        &self,
+        user_principal: Principal,
+        governance: Principal,
+    ) -> Result<Vec<Neuron>> {
+        let governance = self.sns_governance(governance).await;
+
+        let mut neurons = Vec::new();
+        let mut start_page_at = None;
+        loop {
+            let page = governance
+                .list_neurons(ListNeurons {
+                    of_principal: Some(user_principal),
+                    limit: NEURON_PAGE_SIZE,
+                    start_page_at,
+                })
+                .await?
+                .neurons;
+            let page_len = page.len();
+            start_page_at = page.last().and_then(|n| n.id.clone());
+            neurons.extend(page);
+
+            if page_len < NEURON_PAGE_SIZE as usize || start_page_at.is_none() {
+                break;
+            }
+        }
+
+        Ok(neurons)
+    }
+
+    /// Claim status of `user_principal`'s tokens in the given SNS governance
+    pub async fn claim_status(
+        &self,
+        user_principal: Principal,
+        governance: Principal,
+    ) -> Result<ClaimStatus> {
+        let neurons = self.list_all_neurons(user_principal, governance).await?;
+        Ok(ClaimStatus::from_neurons(
+            &neurons,
+            current_epoch().as_secs(),
+        ))
+    }
+
+    /// Claim status of `user_principal`'s tokens for the SNS with the given root,
+    /// e.g. to decide whether a `ClaimTokensRequest` job still has work to do
+    pub async fn claim_status_by_root(
+        &self,
+        user_principal: Principal,
+        token_root: Principal,
+    ) -> Result<ClaimStatus> {
+        let root = self.sns_root(token_root).await;
+        let sns_cans = root.list_sns_canisters(ListSnsCanistersArg {}).await?;
+        let governance = sns_cans.governance.ok_or_else(|| {
+            Error::YralCanister(format!("governance canister not found for {token_root}"))
+        })?;
+
+        self.claim_status(user_principal, governance).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn neuron(stake: u64, dissolve_state: Option<DissolveState>) -> Neuron {
+        Neuron {
+            id: None,
+            staked_maturity_e8s_equivalent: None,
+            permissions: vec![],
+            maturity_e8s_equivalent: 0,
+            cached_neuron_stake_e8s: stake,
+            created_timestamp_seconds: 0,
+            source_nns_neuron_id: None,
+            auto_stake_maturity: None,
+            aging_since_timestamp_seconds: 0,
+            dissolve_state,
+            voting_power_percentage_multiplier: 100,
+            vesting_period_seconds: None,
+            disburse_maturity_in_progress: vec![],
+            followees: vec![],
+            neuron_fees_e8s: 0,
+        }
+    }
+
+    fn dissolved() -> Option<DissolveState> {
+        Some(DissolveState::DissolveDelaySeconds(0))
+    }
+
+    fn dissolving_until(ts: u64) -> Option<DissolveState> {
+        Some(DissolveState::WhenDissolvedTimestampSeconds(ts))
+    }
+
+    fn locked() -> Option<DissolveState> {
+        Some(DissolveState::DissolveDelaySeconds(1_000))
+    }
+
+    #[test]
+    fn no_neurons() {
+        assert_eq!(ClaimStatus::from_neurons(&[], NOW), ClaimStatus::NoNeurons);
+    }
+
+    #[test]
+    fn dissolved_stake_is_claimable() {
+        let neurons = [
+            neuron(100, dissolved()),
+            neuron(50, dissolving_until(NOW)),
+            neuron(25, dissolving_until(NOW + 60)),
+        ];
+        let status = ClaimStatus::from_neurons(&neurons, NOW);
+        assert_eq!(status, ClaimStatus::Unclaimed { claimable_e8s: 150 });
+        assert!(status.needs_claim());
+        assert!(status.is_pending());
+    }
+
+    #[test]
+    fn dissolving_stake_is_claiming() {
+        let neurons = [
+            neuron(50, dissolving_until(NOW + 60)),
+            neuron(25, dissolving_until(NOW + 120)),
+            neuron(1_000, locked()),
+        ];
+        let status = ClaimStatus::from_neurons(&neurons, NOW);
+        assert_eq!(
+            status,
+            ClaimStatus::Claiming {
+                dissolving_e8s: 75,
+                dissolved_at_secs: NOW + 120,
+            }
+        );
+        assert!(!status.needs_claim());
+        assert!(status.is_pending());
+    }
+
+    #[test]
+    fn empty_or_locked_neurons_are_claimed() {
+        let neurons = [
+            neuron(0, dissolved()),
+            neuron(0, dissolving_until(NOW + 60)),
+            neuron(1_000, locked()),
+            neuron(1_000, None),
+        ];
+        let status = ClaimStatus::from_neurons(&neurons, NOW);
+        assert_eq!(status, ClaimStatus::Claimed);
+        assert!(!status.is_pending());
+    }
+}
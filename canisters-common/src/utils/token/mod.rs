@@ -1,10 +1,9 @@
 use canisters_client::ic::USER_INFO_SERVICE_ID;
 use canisters_client::sns_swap::GetInitArg;
-use hon_worker_common::SatsBalanceInfo;
-use hon_worker_common::WithdrawalState;
+use hon_worker_common::{HonWorkerClient, SatsBalanceInfo};
+use hon_worker_common::{WithdrawalLimits, WithdrawalState};
 use pump_n_dump_common::rest::BalanceInfoResponse;
 use std::{fmt::Display, str::FromStr};
-use url::Url;
 
 use balance::{TokenBalance, TokenBalanceOrClaiming};
 use candid::{Nat, Principal};
@@ -18,19 +17,28 @@ use crate::{
     error, Canisters, PndError, Result, CENT_TOKEN_NAME, SATS_TOKEN_NAME, SATS_TOKEN_SYMBOL,
 };
 use canisters_client::{
-    sns_governance::{DissolveState, GetMetadataArg, ListNeurons},
+    sns_governance::GetMetadataArg,
     sns_ledger::{self, Account as LedgerAccount, MetadataValue},
     sns_root::ListSnsCanistersArg,
 };
 use serde::{Deserialize, Serialize};
 pub mod balance;
+pub mod claim;
 pub mod operations;
+pub mod portfolio;
+pub mod preview;
 pub mod types;
 
+pub use claim::ClaimStatus;
 pub use operations::TokenOperations;
-pub use types::{CkBtcOperations, DolrOperations, SatsOperations, TokenOperationsProvider};
+pub use portfolio::Portfolio;
+pub use preview::{TransferFailure, TransferPreview};
+pub use types::{
+    CkBtcOperations, CkUsdcOperations, DolrOperations, SatsOperations, TokenOperationsProvider,
+};
 
 use canisters_client::sns_root::ListSnsCanistersResponse;
+use price_oracle::{Decimal, PriceSource};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TokenMetadata {
@@ -50,6 +58,24 @@ pub struct TokenMetadata {
     pub token_owner: Option<TokenOwner>,
 }
 
+impl TokenMetadata {
+    /// Value of the loaded balance in USD
+    /// returns `None` if the balance isn't loaded, `source` has no price for the token
+    /// or the value is too large for a [`Decimal`]
+    pub async fn usd_value(&self, source: &impl PriceSource) -> Result<Option<Decimal>> {
+        let Some(balance) = self
+            .balance
+            .as_ref()
+            .and_then(|b| b.map_balance_ref(|b| b.clone()))
+        else {
+            return Ok(None);
+        };
+        let price = source.usd_price(&self.symbol).await?;
+
+        Ok(price.and_then(|p| balance.usd_value(p)))
+    }
+}
+
 #[derive(Clone, PartialEq, Serialize, Deserialize, Hash, Eq, Debug)]
 pub enum RootType {
     BTC { ledger: Principal, index: Principal },
@@ -93,11 +119,8 @@ impl Display for RootType {
 pub async fn load_cents_balance(
     user_canister: Principal,
 ) -> std::result::Result<BalanceInfoResponse, PndError> {
-    let balance_url = PUMP_AND_DUMP_WORKER_URL
-        .join(&format!("/balance/{user_canister}"))
-        .expect("Url to be valid");
-
-    let res: BalanceInfoResponse = reqwest::get(balance_url).await?.json().await?;
+    let client = HonWorkerClient::new(PUMP_AND_DUMP_WORKER_URL.clone());
+    let res = client.balance_as(user_canister).await?;
 
     Ok(res)
 }
@@ -105,12 +128,7 @@ pub async fn load_cents_balance(
 pub async fn load_sats_balance(
     user_principal: Principal,
 ) -> std::result::Result<SatsBalanceInfo, PndError> {
-    let url: Url = hon_worker_common::WORKER_URL.parse().unwrap();
-    let balance_url = url
-        .join(&format!("/balance/{user_principal}"))
-        .expect("Url to be valid");
-
-    let res: SatsBalanceInfo = reqwest::get(balance_url).await?.json().await?;
+    let res = HonWorkerClient::default().balance(user_principal).await?;
 
     Ok(res)
 }
@@ -179,13 +197,12 @@ impl<const A: bool> Canisters<A> {
                 let bal_info = load_cents_balance(user_canister).await?;
                 let bal = bal_info.balance.clone();
 
-                let withdrawal_state = if bal_info.withdrawable == 0usize {
-                    WithdrawalState::NeedMoreEarnings(
-                        (bal_info.net_airdrop_reward - bal_info.balance) + 1e6 as usize,
-                    )
-                } else {
-                    WithdrawalState::Value(bal_info.withdrawable)
-                };
+                let withdrawal_state = WithdrawalState::compute(
+                    &bal_info.balance,
+                    &bal_info.net_airdrop_reward,
+                    &bal_info.withdrawable,
+                    WithdrawalLimits::CENTS,
+                );
 
                 Ok(Some(TokenMetadata {
                     logo_b64: "/img/yral/cents.webp".to_string(),
@@ -198,7 +215,7 @@ impl<const A: bool> Canisters<A> {
                     root: None,
                     ledger: Principal::anonymous(),
                     index: Principal::anonymous(),
-                    decimals: 8,
+                    decimals: 6,
                     token_owner: None,
                 }))
             }
@@ -219,7 +236,12 @@ impl<const A: bool> Canisters<A> {
                         bal.clone().into(),
                         0,
                     ))),
-                    withdrawable_state: Some(WithdrawalState::Value(bal.into())),
+                    withdrawable_state: Some(WithdrawalState::compute(
+                        &bal.clone().into(),
+                        &Nat::from(0u32),
+                        &bal.into(),
+                        WithdrawalLimits::SATS,
+                    )),
                     fees: TokenBalance::new(0u32.into(), 0),
                     root: None,
                     ledger: Principal::anonymous(),
@@ -314,42 +336,26 @@ impl<const A: bool> Canisters<A> {
         };
         // Balance > 0 -> Token is already claimed
         let balance_e8s = ledger.icrc_1_balance_of(acc).await?;
-        let ready_balance = |e8s| {
-            Ok(TokenBalanceOrClaiming::new(TokenBalance::new(
-                e8s, decimals,
-            )))
-        };
         if balance_e8s > 0u8 {
-            return ready_balance(balance_e8s);
-        }
-
-        // if balance is 0 we may not have completed claiming
-        let governance = self.sns_governance(governance).await;
-        let neurons = governance
-            .list_neurons(ListNeurons {
-                of_principal: Some(user_principal),
-                limit: 10,
-                start_page_at: None,
-            })
-            .await?
-            .neurons;
-
-        if neurons.len() < 2 || neurons[1].cached_neuron_stake_e8s == 0 {
-            return ready_balance(balance_e8s);
-        }
-
-        if matches!(
-            neurons[1].dissolve_state.as_ref(),
-            Some(DissolveState::DissolveDelaySeconds(0))
-        ) {
-            return Ok(TokenBalanceOrClaiming::claiming());
+            return Ok(TokenBalanceOrClaiming::new(TokenBalance::new(
+                balance_e8s,
+                decimals,
+            )));
         }
 
-        if neurons[0].cached_neuron_stake_e8s == 0 {
-            return ready_balance(balance_e8s);
-        }
+        // Nothing in the ledger yet, check the user's neurons:
+        // - `Unclaimed` stake is being disbursed to the ledger -> claiming ("Processing")
+        // - `Claiming` stake is still dissolving and won't arrive before the dissolve date,
+        //   `NoNeurons` and `Claimed` have nothing on the way -> zero balance
+        // the status is attached either way, see `TokenBalanceOrClaiming::claim_status`
+        let claim_status = self.claim_status(user_principal, governance).await?;
+        let balance = if claim_status.needs_claim() {
+            TokenBalanceOrClaiming::claiming()
+        } else {
+            TokenBalanceOrClaiming::new(TokenBalance::new(balance_e8s, decimals))
+        };
 
-        Ok(TokenBalanceOrClaiming::claiming())
+        Ok(balance.with_claim_status(claim_status))
     }
 
     pub async fn get_ck_metadata(
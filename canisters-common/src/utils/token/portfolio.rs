@@ -0,0 +1,91 @@
+use candid::Principal;
+use futures_util::{stream, StreamExt};
+use price_oracle::{Decimal, PriceSource};
+
+use super::{RootType, TokenMetadata};
+use crate::{Canisters, Error, Result};
+
+/// Default number of tokens loaded in parallel by [`Canisters::load_portfolio`]
+pub const PORTFOLIO_LOAD_CONCURRENCY: usize = 6;
+
+/// A user's wallet, loaded token by token
+/// A failure to load one token doesn't prevent the others from loading
+#[derive(Debug, Default)]
+pub struct Portfolio {
+    /// Successfully loaded tokens, in the order they were requested
+    pub tokens: Vec<(RootType, TokenMetadata)>,
+    /// Tokens for which no metadata exists (e.g. user has no CENTS canister)
+    pub missing: Vec<RootType>,
+    /// Tokens that failed to load
+    pub errors: Vec<(RootType, Error)>,
+}
+
+impl Portfolio {
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn get(&self, root: &RootType) -> Option<&TokenMetadata> {
+        self.tokens
+            .iter()
+            .find_map(|(r, metadata)| (r == root).then_some(metadata))
+    }
+
+    /// Total value of the loaded tokens in USD
+    /// tokens without a known price are skipped
+    pub async fn usd_total(&self, source: &impl PriceSource) -> Result<Decimal> {
+        let mut total = Decimal::ZERO;
+        for (_, metadata) in &self.tokens {
+            if let Some(value) = metadata.usd_value(source).await? {
+                total += value;
+            }
+        }
+
+        Ok(total)
+    }
+}
+
+impl<const A: bool> Canisters<A> {
+    /// Load metadata and balances of `user` for every token in `roots`
+    /// see [`Self::load_portfolio_with_concurrency`]
+    pub async fn load_portfolio(
+        &self,
+        user: Principal,
+        roots: impl IntoIterator<Item = RootType>,
+    ) -> Portfolio {
+        self.load_portfolio_with_concurrency(user, roots, PORTFOLIO_LOAD_CONCURRENCY)
+            .await
+    }
+
+    /// Load metadata and balances of `user` for every token in `roots`,
+    /// with at most `concurrency` tokens being fetched at once
+    pub async fn load_portfolio_with_concurrency(
+        &self,
+        user: Principal,
+        roots: impl IntoIterator<Item = RootType>,
+        concurrency: usize,
+    ) -> Portfolio {
+        let mut results = stream::iter(roots)
+            .map(|root| async move {
+                let res = self
+                    .token_metadata_by_root_type(Some(user), root.clone())
+                    .await;
+                (root, res)
+            })
+            .buffered(concurrency.max(1));
+
+        let mut portfolio = Portfolio::default();
+        while let Some((root, res)) = results.next().await {
+            match res {
+                Ok(Some(metadata)) => portfolio.tokens.push((root, metadata)),
+                Ok(None) => portfolio.missing.push(root),
+                Err(e) => {
+                    log::warn!("failed to load {root} for {user}: {e}");
+                    portfolio.errors.push((root, e));
+                }
+            }
+        }
+
+        portfolio
+    }
+}
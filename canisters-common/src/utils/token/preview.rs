@@ -0,0 +1,280 @@
+use candid::{Nat, Principal};
+use serde::{Deserialize, Serialize};
+
+use super::{balance::TokenBalance, RootType, TokenMetadata};
+use crate::{Canisters, Result, TokenBalanceError};
+
+/// Reason a previewed transfer would be rejected
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransferFailure {
+    /// The token can't be sent to another user (e.g. CENTS)
+    NotTransferable,
+    /// The sender's tokens are still being claimed from the SNS swap
+    Claiming,
+    /// The balance doesn't cover the amount plus the fee
+    InsufficientFunds { shortfall: TokenBalance },
+    /// The sender's balance isn't loaded yet, so the outcome is unknown
+    BalanceUnknown,
+}
+
+/// Outcome of a transfer, computed before submitting it to the ledger
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TransferPreview {
+    pub amount: TokenBalance,
+    pub fee: TokenBalance,
+    /// amount + fee, i.e. what leaves the sender's account
+    pub total_debit: TokenBalance,
+    /// sender's balance before the transfer
+    pub balance: Option<TokenBalance>,
+    /// sender's balance after the transfer, `None` if the transfer would fail
+    pub resulting_balance: Option<TokenBalance>,
+    pub failure: Option<TransferFailure>,
+}
+
+impl TransferPreview {
+    /// Preview sending `amount` out of `balance`, with `fee` charged on top
+    /// fails if the balances don't all have the same decimals
+    pub fn new(
+        balance: TokenBalance,
+        fee: TokenBalance,
+        amount: TokenBalance,
+    ) -> Result<Self, TokenBalanceError> {
+        let total_debit = amount.checked_add(&fee)?;
+
+        let (resulting_balance, failure) = match balance.checked_sub(&total_debit) {
+            Ok(resulting_balance) => (Some(resulting_balance), None),
+            Err(TokenBalanceError::Underflow) => {
+                let shortfall = total_debit.checked_sub(&balance)?;
+                (None, Some(TransferFailure::InsufficientFunds { shortfall }))
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            amount,
+            fee,
+            total_debit,
+            balance: Some(balance),
+            resulting_balance,
+            failure,
+        })
+    }
+
+    fn failed(
+        fee: TokenBalance,
+        amount: TokenBalance,
+        failure: TransferFailure,
+    ) -> Result<Self, TokenBalanceError> {
+        Ok(Self {
+            total_debit: amount.checked_add(&fee)?,
+            amount,
+            fee,
+            balance: None,
+            resulting_balance: None,
+            failure: Some(failure),
+        })
+    }
+
+    /// The transfer would be rejected
+    /// `false` if the outcome is unknown, see [`Self::is_unknown`]
+    pub fn will_fail(&self) -> bool {
+        self.failure.is_some() && !self.is_unknown()
+    }
+
+    /// The sender's balance isn't loaded yet
+    pub fn is_unknown(&self) -> bool {
+        self.failure == Some(TransferFailure::BalanceUnknown)
+    }
+}
+
+impl TokenMetadata {
+    /// Fee charged by the ledger for a single transfer of this token
+    /// returns `None` if the token can't be transferred between users
+    pub fn transfer_fee(&self, root_type: &RootType) -> Option<TokenBalance> {
+        match root_type {
+            // CENTS can only be withdrawn, not sent
+            RootType::CENTS => None,
+            // SATS live off-ledger in the worker, transfers don't cost anything
+            RootType::SATS => Some(TokenBalance::new(0u32.into(), 0)),
+            // ck tokens report their fee in the token's own decimals
+            RootType::BTC { .. } | RootType::USDC { .. } => Some(self.fees.clone()),
+            RootType::Other(_) => Some(TokenBalance::new(self.fees.e8s.clone(), self.decimals)),
+        }
+    }
+
+    /// Maximum amount the holder can send, after accounting for the fee
+    /// returns `None` if the balance is unknown, still being claimed or the token isn't transferable
+    /// and fails if the balance and fee have different decimals
+    pub fn max_sendable(
+        &self,
+        root_type: &RootType,
+    ) -> Result<Option<TokenBalance>, TokenBalanceError> {
+        let Some(fee) = self.transfer_fee(root_type) else {
+            return Ok(None);
+        };
+        let Some(balance) = self
+            .balance
+            .as_ref()
+            .and_then(|b| b.map_balance_ref(|b| b.clone()))
+        else {
+            return Ok(None);
+        };
+
+        match balance.checked_sub(&fee) {
+            Ok(max) => Ok(Some(max)),
+            Err(TokenBalanceError::Underflow) => {
+                Ok(Some(TokenBalance::new(Nat::from(0u32), balance.decimals())))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Preview sending `amount` of this token, using the holder's loaded balance
+    /// the preview is [`TransferFailure::BalanceUnknown`] if the balance isn't loaded
+    pub fn transfer_preview(
+        &self,
+        root_type: &RootType,
+        amount: TokenBalance,
+    ) -> Result<TransferPreview, TokenBalanceError> {
+        let Some(fee) = self.transfer_fee(root_type) else {
+            let fee = TokenBalance::new(Nat::from(0u32), amount.decimals());
+            return TransferPreview::failed(fee, amount, TransferFailure::NotTransferable);
+        };
+
+        match self
+            .balance
+            .as_ref()
+            .map(|b| b.map_balance_ref(|b| b.clone()))
+        {
+            Some(None) => TransferPreview::failed(fee, amount, TransferFailure::Claiming),
+            Some(Some(balance)) => TransferPreview::new(balance, fee, amount),
+            None => TransferPreview::failed(fee, amount, TransferFailure::BalanceUnknown),
+        }
+    }
+}
+
+impl<const A: bool> Canisters<A> {
+    /// Preview a transfer of `amount` from `sender`, fetching the current balance and fee
+    /// returns `None` if the token's metadata couldn't be loaded
+    /// and fails if the amount, fee and balance have different decimals
+    pub async fn transfer_preview(
+        &self,
+        root_type: RootType,
+        sender: Principal,
+        amount: TokenBalance,
+    ) -> Result<Option<TransferPreview>> {
+        let Some(metadata) = self
+            .token_metadata_by_root_type(Some(sender), root_type.clone())
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(metadata.transfer_preview(&root_type, amount)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+
+    use super::*;
+    use crate::utils::token::balance::TokenBalanceOrClaiming;
+
+    fn bal(e8s: u64, decimals: u8) -> TokenBalance {
+        TokenBalance::new(Nat::from(e8s), decimals)
+    }
+
+    fn metadata(balance: Option<TokenBalanceOrClaiming>, fees: TokenBalance) -> TokenMetadata {
+        TokenMetadata {
+            logo_b64: String::new(),
+            name: "ckBTC".into(),
+            description: String::new(),
+            symbol: "ckBTC".into(),
+            balance,
+            withdrawable_state: None,
+            decimals: fees.decimals(),
+            fees,
+            root: None,
+            ledger: Principal::anonymous(),
+            index: Principal::anonymous(),
+            token_owner: None,
+        }
+    }
+
+    const BTC: RootType = RootType::BTC {
+        ledger: Principal::anonymous(),
+        index: Principal::anonymous(),
+    };
+
+    #[test]
+    fn preview_exact_balance() {
+        let preview = TransferPreview::new(bal(110, 8), bal(10, 8), bal(100, 8)).unwrap();
+        assert!(!preview.will_fail());
+        assert_eq!(preview.total_debit, bal(110, 8));
+        assert_eq!(preview.resulting_balance, Some(bal(0, 8)));
+    }
+
+    #[test]
+    fn preview_balance_below_fee() {
+        let preview = TransferPreview::new(bal(5, 8), bal(10, 8), bal(100, 8)).unwrap();
+        assert!(preview.will_fail());
+        assert_eq!(preview.resulting_balance, None);
+        assert_eq!(
+            preview.failure,
+            Some(TransferFailure::InsufficientFunds {
+                shortfall: bal(105, 8)
+            })
+        );
+    }
+
+    #[test]
+    fn preview_rejects_mismatched_decimals() {
+        assert_eq!(
+            TransferPreview::new(bal(1_000, 8), bal(10, 6), bal(100, 8)).unwrap_err(),
+            TokenBalanceError::DecimalsMismatch { left: 8, right: 6 }
+        );
+        assert_eq!(
+            TransferPreview::new(bal(1_000, 6), bal(10, 8), bal(100, 8)).unwrap_err(),
+            TokenBalanceError::DecimalsMismatch { left: 6, right: 8 }
+        );
+    }
+
+    #[test]
+    fn preview_unloaded_balance_is_unknown() {
+        let preview = metadata(None, bal(10, 8))
+            .transfer_preview(&BTC, bal(100, 8))
+            .unwrap();
+        assert!(preview.is_unknown());
+        assert!(!preview.will_fail());
+    }
+
+    #[test]
+    fn max_sendable_exact_balance() {
+        let token = metadata(Some(TokenBalanceOrClaiming::new(bal(110, 8))), bal(10, 8));
+        assert_eq!(token.max_sendable(&BTC).unwrap(), Some(bal(100, 8)));
+
+        let token = metadata(Some(TokenBalanceOrClaiming::new(bal(10, 8))), bal(10, 8));
+        assert_eq!(token.max_sendable(&BTC).unwrap(), Some(bal(0, 8)));
+    }
+
+    #[test]
+    fn max_sendable_balance_below_fee() {
+        let token = metadata(Some(TokenBalanceOrClaiming::new(bal(5, 8))), bal(10, 8));
+        assert_eq!(token.max_sendable(&BTC).unwrap(), Some(bal(0, 8)));
+    }
+
+    #[test]
+    fn max_sendable_rejects_mismatched_decimals() {
+        let token = metadata(Some(TokenBalanceOrClaiming::new(bal(110, 6))), bal(10, 8));
+        assert_eq!(
+            token.max_sendable(&BTC).unwrap_err(),
+            TokenBalanceError::DecimalsMismatch { left: 6, right: 8 }
+        );
+    }
+
+    #[test]
+    fn max_sendable_unknown_balance() {
+        assert_eq!(metadata(None, bal(10, 8)).max_sendable(&BTC).unwrap(), None);
+    }
+}
@@ -1,12 +1,16 @@
 use candid::{Nat, Principal};
-use hon_worker_common::SatsBalanceUpdateRequestV2;
+use hon_worker_common::{
+    HonWorkerClient, HonWorkerClientError, SatsBalanceUpdateRequestV2, WorkerError,
+};
 use num_bigint::{BigInt, BigUint, Sign};
-use reqwest::Client;
-use url::Url;
 
 use super::balance::TokenBalance;
 use super::operations::TokenOperations;
-use crate::{consts::DOLR_AI_LEDGER_CANISTER, error::Error, Result};
+use crate::{
+    consts::{CKUSDC_DECIMALS, CKUSDC_FEE_E6S, CKUSDC_LEDGER, DOLR_AI_LEDGER_CANISTER},
+    error::{Error, HonError},
+    Result,
+};
 use canisters_client::{
     ic::USER_INFO_SERVICE_ID,
     sns_ledger::{self, Account as LedgerAccount},
@@ -15,129 +19,90 @@ use canisters_client::{
 
 // ckBTC transfer types - no longer needed as we're using direct IC transfers
 
+/// How many times a SATS balance update is retried after losing a compare-and-swap race
+pub const SATS_CAS_MAX_RETRIES: u32 = 3;
+
 #[derive(Clone)]
 pub struct SatsOperations {
     jwt_token: Option<String>,
-    client: Client,
+    worker: HonWorkerClient,
 }
 
 impl SatsOperations {
     pub fn new(jwt_token: Option<String>) -> Self {
-        Self {
-            jwt_token,
-            client: Client::new(),
+        let worker = match jwt_token.clone() {
+            Some(jwt) => HonWorkerClient::default().with_jwt(jwt),
+            None => HonWorkerClient::default(),
+        };
+        Self { jwt_token, worker }
+    }
+
+    /// Use `worker` instead of the client for [`hon_worker_common::WORKER_URL`]
+    /// e.g. to test against a local worker
+    pub fn with_worker(mut self, worker: HonWorkerClient) -> Self {
+        self.worker = match self.jwt_token.clone() {
+            Some(jwt) => worker.with_jwt(jwt),
+            None => worker,
+        };
+        self
+    }
+
+    /// Apply `delta` to the user's balance with compare-and-swap on the previous balance
+    /// retries with the balance reported by the worker if another update won the race
+    async fn update_balance(&self, user_principal: Principal, delta: BigInt) -> Result<()> {
+        if self.jwt_token.is_none() {
+            return Err(Error::YralCanister(
+                "JWT token required for balance update".to_string(),
+            ));
+        }
+
+        let mut previous_balance = BigUint::from(self.load_balance(user_principal).await?.e8s);
+        let mut attempt = 0;
+        loop {
+            let worker_req = SatsBalanceUpdateRequestV2 {
+                previous_balance,
+                delta: delta.clone(),
+                is_airdropped: false,
+            };
+
+            match self
+                .worker
+                .update_balance_v2(user_principal, &worker_req)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(HonWorkerClientError::Worker(WorkerError::BalanceTransactionConflict {
+                    new_balance,
+                })) if attempt < SATS_CAS_MAX_RETRIES => {
+                    attempt += 1;
+                    previous_balance = new_balance;
+                }
+                Err(e) => return Err(HonError::from(e).into()),
+            }
         }
     }
 }
 
 impl TokenOperations for SatsOperations {
     async fn load_balance(&self, user_principal: Principal) -> Result<TokenBalance> {
-        let url: Url = hon_worker_common::WORKER_URL.parse().unwrap();
-        let balance_url = url
-            .join(&format!("/balance/{user_principal}"))
-            .expect("Url to be valid");
-
-        let res: hon_worker_common::SatsBalanceInfo = self
-            .client
-            .get(balance_url)
-            .send()
-            .await
-            .map_err(|e| Error::YralCanister(e.to_string()))?
-            .json()
+        let res = self
+            .worker
+            .balance(user_principal)
             .await
-            .map_err(|e| Error::YralCanister(e.to_string()))?;
+            .map_err(HonError::from)?;
 
         Ok(TokenBalance::new(res.balance.into(), 0))
     }
 
     async fn deduct_balance(&self, user_principal: Principal, amount: u64) -> Result<u64> {
-        let jwt_token = self.jwt_token.as_ref().ok_or_else(|| {
-            Error::YralCanister("JWT token required for deduct operation".to_string())
-        })?;
-
-        // First, load the current balance
-        let current_balance = self.load_balance(user_principal).await?;
-        let previous_balance = BigUint::from(current_balance.e8s);
-
-        // Create negative delta for deduction
         let delta = BigInt::from_biguint(Sign::Minus, BigUint::from(amount));
-
-        let url: Url = hon_worker_common::WORKER_URL.parse().unwrap();
-        let deduct_url = url
-            .join(&format!("/v2/update_balance/{user_principal}"))
-            .expect("Url to be valid");
-
-        let worker_req = SatsBalanceUpdateRequestV2 {
-            previous_balance,
-            delta,
-            is_airdropped: false,
-        };
-
-        let res = self
-            .client
-            .post(deduct_url)
-            .bearer_auth(jwt_token)
-            .json(&worker_req)
-            .send()
-            .await
-            .map_err(|e| Error::YralCanister(e.to_string()))?;
-
-        if res.status().is_success() {
-            Ok(amount)
-        } else {
-            let error_text = res
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::YralCanister(format!(
-                "Failed to deduct balance: {error_text}"
-            )))
-        }
+        self.update_balance(user_principal, delta).await?;
+        Ok(amount)
     }
 
     async fn add_balance(&self, user_principal: Principal, amount: u64) -> Result<()> {
-        let jwt_token = self.jwt_token.as_ref().ok_or_else(|| {
-            Error::YralCanister("JWT token required for add operation".to_string())
-        })?;
-
-        // First, load the current balance
-        let current_balance = self.load_balance(user_principal).await?;
-        let previous_balance = BigUint::from(current_balance.e8s);
-
-        // Create positive delta for addition
-        let delta = BigInt::from(amount);
-
-        let url: Url = hon_worker_common::WORKER_URL.parse().unwrap();
-        let add_url = url
-            .join(&format!("/v2/update_balance/{user_principal}"))
-            .expect("Url to be valid");
-
-        let worker_req = SatsBalanceUpdateRequestV2 {
-            previous_balance,
-            delta,
-            is_airdropped: false,
-        };
-
-        let res = self
-            .client
-            .post(add_url)
-            .bearer_auth(jwt_token)
-            .json(&worker_req)
-            .send()
+        self.update_balance(user_principal, BigInt::from(amount))
             .await
-            .map_err(|e| Error::YralCanister(e.to_string()))?;
-
-        if res.status().is_success() {
-            Ok(())
-        } else {
-            let error_text = res
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(Error::YralCanister(format!(
-                "Failed to add balance: {error_text}"
-            )))
-        }
     }
 }
 
@@ -372,6 +337,7 @@ impl TokenOperations for CkBtcOperations {
         Ok(TokenBalance::new(balance, 8))
     }
 
+    /// use [`crate::Canisters::withdraw_btc`] to move ckBTC out to bitcoin
     async fn deduct_balance(&self, _user_principal: Principal, _amount: u64) -> Result<u64> {
         Err(Error::YralCanister(
             "ckBTC deduction not supported".to_string(),
@@ -453,11 +419,152 @@ impl TokenOperations for CkBtcOperations {
     }
 }
 
+#[derive(Clone)]
+pub struct CkUsdcOperations {
+    admin_agent: ic_agent::Agent,
+    user_agent: Option<ic_agent::Agent>,
+}
+
+impl CkUsdcOperations {
+    pub fn new(admin_agent: ic_agent::Agent) -> Self {
+        Self {
+            admin_agent,
+            user_agent: None,
+        }
+    }
+
+    pub fn with_user_agent(admin_agent: ic_agent::Agent, user_agent: ic_agent::Agent) -> Self {
+        Self {
+            admin_agent,
+            user_agent: Some(user_agent),
+        }
+    }
+
+    fn ledger_id() -> Result<Principal> {
+        Principal::from_text(CKUSDC_LEDGER).map_err(|e| Error::YralCanister(e.to_string()))
+    }
+}
+
+impl TokenOperations for CkUsdcOperations {
+    async fn load_balance(&self, user_principal: Principal) -> Result<TokenBalance> {
+        let agent = self.user_agent.as_ref().unwrap_or(&self.admin_agent);
+        let ledger = sns_ledger::SnsLedger(Self::ledger_id()?, agent);
+
+        let balance = ledger
+            .icrc_1_balance_of(LedgerAccount {
+                owner: user_principal,
+                subaccount: None,
+            })
+            .await
+            .map_err(|e| Error::YralCanister(e.to_string()))?;
+
+        Ok(TokenBalance::new(balance, CKUSDC_DECIMALS))
+    }
+
+    /// The user pays the ledger fee on top of `amount`
+    /// without a user agent, the user must have approved `amount` + fee for the admin
+    async fn deduct_balance(&self, user_principal: Principal, amount: u64) -> Result<u64> {
+        let ledger_id = Self::ledger_id()?;
+        let admin_principal = self
+            .admin_agent
+            .get_principal()
+            .map_err(|e| Error::YralCanister(e.to_string()))?;
+        let admin_account = LedgerAccount {
+            owner: admin_principal,
+            subaccount: None,
+        };
+
+        let res = match &self.user_agent {
+            Some(user_agent) => {
+                let ledger = sns_ledger::SnsLedger(ledger_id, user_agent);
+                match ledger
+                    .icrc_1_transfer(sns_ledger::TransferArg {
+                        from_subaccount: None,
+                        to: admin_account,
+                        amount: amount.into(),
+                        fee: Some(CKUSDC_FEE_E6S.into()),
+                        memo: None,
+                        created_at_time: None,
+                    })
+                    .await
+                    .map_err(|e| Error::YralCanister(e.to_string()))?
+                {
+                    sns_ledger::TransferResult::Ok(block) => Ok(block),
+                    sns_ledger::TransferResult::Err(e) => Err(format!("{e:?}")),
+                }
+            }
+            None => {
+                let ledger = sns_ledger::SnsLedger(ledger_id, &self.admin_agent);
+                match ledger
+                    .icrc_2_transfer_from(sns_ledger::TransferFromArgs {
+                        spender_subaccount: None,
+                        from: LedgerAccount {
+                            owner: user_principal,
+                            subaccount: None,
+                        },
+                        to: admin_account,
+                        amount: amount.into(),
+                        fee: Some(CKUSDC_FEE_E6S.into()),
+                        memo: None,
+                        created_at_time: None,
+                    })
+                    .await
+                    .map_err(|e| Error::YralCanister(e.to_string()))?
+                {
+                    sns_ledger::TransferFromResult::Ok(block) => Ok(block),
+                    sns_ledger::TransferFromResult::Err(e) => Err(format!("{e:?}")),
+                }
+            }
+        };
+
+        res.map(|_| amount)
+            .map_err(|e| Error::YralCanister(format!("ckUSDC transfer failed: {e}")))
+    }
+
+    async fn add_balance(&self, user_principal: Principal, amount: u64) -> Result<()> {
+        self.add_balance_with_memo(user_principal, amount, None)
+            .await
+    }
+
+    /// The admin pays the ledger fee, the user receives the full `amount`
+    async fn add_balance_with_memo(
+        &self,
+        user_principal: Principal,
+        amount: u64,
+        memo: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let ledger = sns_ledger::SnsLedger(Self::ledger_id()?, &self.admin_agent);
+
+        let res = ledger
+            .icrc_1_transfer(sns_ledger::TransferArg {
+                to: LedgerAccount {
+                    owner: user_principal,
+                    subaccount: None,
+                },
+                amount: amount.into(),
+                fee: Some(CKUSDC_FEE_E6S.into()),
+                memo: memo.map(Into::into),
+                from_subaccount: None,
+                created_at_time: None,
+            })
+            .await
+            .map_err(|e| Error::YralCanister(e.to_string()))?;
+
+        match res {
+            sns_ledger::TransferResult::Ok(_) => Ok(()),
+            sns_ledger::TransferResult::Err(e) => Err(Error::YralCanister(format!(
+                "ckUSDC transfer failed: {e:?}"
+            ))),
+        }
+    }
+}
+
 #[enum_dispatch::enum_dispatch(TokenOperations)]
 #[allow(clippy::large_enum_variant)]
 pub enum TokenOperationsProvider {
     Sats(SatsOperations),
     Dolr(DolrOperations),
     CkBtc(CkBtcOperations),
+    CkUsdc(CkUsdcOperations),
     YralProSubscription(YralProSubscription),
 }
@@ -1,6 +1,6 @@
 use std::fmt::{self, Display, Formatter};
 
-use candid::Principal;
+use candid::{Nat, Principal};
 use serde::{Deserialize, Serialize};
 
 use super::token::balance::TokenBalance;
@@ -21,6 +21,16 @@ pub enum TxnInfoType {
     Transfer { from: Principal, to: Principal }, // only for public transaction
 }
 
+/// Kind of a [`TxnInfoType`], without the parties involved
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum TxnKind {
+    Mint,
+    Sent,
+    Burn,
+    Received,
+    Transfer,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct TxnInfoWallet {
     pub tag: TxnInfoType,
@@ -40,6 +50,30 @@ impl From<TxnInfoType> for TxnDirection {
 }
 
 impl TxnInfoType {
+    pub fn kind(self) -> TxnKind {
+        match self {
+            TxnInfoType::Mint { .. } => TxnKind::Mint,
+            TxnInfoType::Sent { .. } => TxnKind::Sent,
+            TxnInfoType::Burn { .. } => TxnKind::Burn,
+            TxnInfoType::Received { .. } => TxnKind::Received,
+            TxnInfoType::Transfer { .. } => TxnKind::Transfer,
+        }
+    }
+
+    /// (from, to) of the transaction, if known
+    pub fn parties(self) -> (Option<Principal>, Option<Principal>) {
+        match self {
+            TxnInfoType::Mint { to } | TxnInfoType::Sent { to } => (None, Some(to)),
+            TxnInfoType::Burn { from } | TxnInfoType::Received { from } => (Some(from), None),
+            TxnInfoType::Transfer { from, to } => (Some(from), Some(to)),
+        }
+    }
+
+    pub fn involves(self, principal: Principal) -> bool {
+        let (from, to) = self.parties();
+        from == Some(principal) || to == Some(principal)
+    }
+
     pub fn to_text(self) -> &'static str {
         match self {
             TxnInfoType::Burn { .. } => "Burned",
@@ -56,3 +90,104 @@ impl Display for TxnInfoType {
         f.write_str(self.to_text())
     }
 }
+
+/// Filter over [`TxnInfoWallet`]s, every unset field matches everything
+#[derive(Clone, Default, Debug)]
+pub struct TxnFilter {
+    /// only keep these kinds of transactions, all if empty
+    pub kinds: Vec<TxnKind>,
+    /// only keep transactions from or to this principal
+    pub counterparty: Option<Principal>,
+    /// inclusive, in nanoseconds
+    pub from_ns: Option<u64>,
+    /// exclusive, in nanoseconds
+    pub until_ns: Option<u64>,
+    /// minimum amount in e8s (base units of the token)
+    pub min_amount: Option<Nat>,
+}
+
+impl TxnFilter {
+    pub fn kinds(mut self, kinds: impl IntoIterator<Item = TxnKind>) -> Self {
+        self.kinds = kinds.into_iter().collect();
+        self
+    }
+
+    pub fn counterparty(mut self, counterparty: Principal) -> Self {
+        self.counterparty = Some(counterparty);
+        self
+    }
+
+    pub fn time_range(mut self, from_ns: Option<u64>, until_ns: Option<u64>) -> Self {
+        self.from_ns = from_ns;
+        self.until_ns = until_ns;
+        self
+    }
+
+    pub fn min_amount(mut self, min_amount: impl Into<Nat>) -> Self {
+        self.min_amount = Some(min_amount.into());
+        self
+    }
+
+    pub fn matches(&self, txn: &TxnInfoWallet) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&txn.tag.kind()))
+            && self.counterparty.is_none_or(|p| txn.tag.involves(p))
+            && self.from_ns.is_none_or(|from| txn.timestamp >= from)
+            && self.until_ns.is_none_or(|until| txn.timestamp < until)
+            && self
+                .min_amount
+                .as_ref()
+                .is_none_or(|min| &txn.amount.e8s >= min)
+    }
+}
+
+/// A transaction along with the token it belongs to
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TokenTxnInfo {
+    /// ledger (or index) canister the transaction was fetched from
+    pub token: Principal,
+    pub txn: TxnInfoWallet,
+}
+
+/// Merge the histories of multiple tokens into a single history, newest first
+pub fn merge_txn_histories(
+    histories: impl IntoIterator<Item = (Principal, Vec<TxnInfoWallet>)>,
+) -> Vec<TokenTxnInfo> {
+    let mut merged: Vec<_> = histories
+        .into_iter()
+        .flat_map(|(token, txns)| txns.into_iter().map(move |txn| TokenTxnInfo { token, txn }))
+        .collect();
+    merged.sort_by(|a, b| {
+        b.txn
+            .timestamp
+            .cmp(&a.txn.timestamp)
+            .then_with(|| b.txn.id.cmp(&a.txn.id))
+    });
+    merged
+}
+
+const CSV_HEADER: &str = "token,id,timestamp_ns,type,from,to,amount";
+
+/// Export transactions as CSV, one row per transaction
+/// amounts are in whole tokens
+pub fn export_txns_csv(txns: &[TokenTxnInfo]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for TokenTxnInfo { token, txn } in txns {
+        let (from, to) = txn.tag.parties();
+        let from = from.map(|p| p.to_text()).unwrap_or_default();
+        let to = to.map(|p| p.to_text()).unwrap_or_default();
+        csv.push_str(&format!(
+            "{token},{},{},{},{from},{to},{}\n",
+            txn.id,
+            txn.timestamp,
+            txn.tag,
+            txn.amount.humanize_float(),
+        ));
+    }
+    csv
+}
+
+/// Export transactions as a JSON array
+pub fn export_txns_json(txns: &[TokenTxnInfo]) -> serde_json::Result<String> {
+    serde_json::to_string(txns)
+}
@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+
+use futures_util::{stream, Stream};
+use web_time::Duration;
+
+use crate::{
+    cursored_data::{
+        transaction::{IndexOrLedger, TxnHistory},
+        CursoredDataProvider,
+    },
+    Result,
+};
+
+use super::{time::sleep, transaction::TxnInfoWallet};
+
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(10);
+/// Upper bound for the delay between polls after repeated failures
+const MAX_WATCH_BACKOFF: Duration = Duration::from_secs(5 * 60);
+const WATCH_PAGE_SIZE: usize = 20;
+
+/// Polls a [`TxnHistory`] and yields transactions that appear after the watcher was started
+pub struct TxnWatcher {
+    history: TxnHistory,
+    interval: Duration,
+    page_size: usize,
+    primed: bool,
+    last_seen_id: Option<u64>,
+    last_tip: Option<u64>,
+}
+
+impl TxnWatcher {
+    pub fn new(history: TxnHistory) -> Self {
+        Self {
+            history,
+            interval: DEFAULT_WATCH_INTERVAL,
+            page_size: WATCH_PAGE_SIZE,
+            primed: false,
+            last_seen_id: None,
+            last_tip: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Fetch new transactions since the last poll, oldest first
+    /// the first poll only records the current position and yields nothing
+    pub async fn poll(&mut self) -> Result<Vec<TxnInfoWallet>> {
+        // avoid paging the ledger if its tip hasn't moved
+        let mut tip = None;
+        if let IndexOrLedger::Ledger(ledger) = self.history.source {
+            let latest = self.history.get_latest_ledger_transaction(ledger).await?;
+            if self.last_tip == Some(latest) {
+                return Ok(vec![]);
+            }
+            tip = Some(latest);
+        }
+
+        let mut new_txns = Vec::new();
+        let mut start = 0;
+        loop {
+            let page = self
+                .history
+                .get_by_cursor(start, start + self.page_size)
+                .await?;
+
+            if !self.primed {
+                self.primed = true;
+                self.last_seen_id = page.data.first().map(|txn| txn.id);
+                self.last_tip = tip.or(self.last_tip);
+                return Ok(vec![]);
+            }
+
+            let page_len = page.data.len();
+            let mut caught_up = false;
+            for txn in page.data {
+                if self.last_seen_id.is_some_and(|last| txn.id <= last) {
+                    caught_up = true;
+                    break;
+                }
+                new_txns.push(txn);
+            }
+            if caught_up || page.end || page_len == 0 {
+                break;
+            }
+            start += self.page_size;
+        }
+
+        // only remember the tip once every page was fetched,
+        // a failed poll is retried from scratch
+        self.last_tip = tip.or(self.last_tip);
+        if let Some(newest) = new_txns.first() {
+            self.last_seen_id = Some(newest.id);
+        }
+        new_txns.reverse();
+
+        Ok(new_txns)
+    }
+
+    /// Delay before the next poll after `failures` consecutive failed polls
+    fn backoff(&self, failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+        self.interval
+            .saturating_mul(factor)
+            .min(MAX_WATCH_BACKOFF.max(self.interval))
+    }
+
+    /// Stream of new transactions, polling every `interval`
+    ///
+    /// polling errors are yielded and watching continues after an exponential backoff
+    pub fn into_stream(self) -> impl Stream<Item = Result<TxnInfoWallet>> {
+        stream::unfold(
+            (self, VecDeque::new(), 0u32),
+            |(mut watcher, mut pending, mut failures)| async move {
+                loop {
+                    if let Some(txn) = pending.pop_front() {
+                        return Some((Ok(txn), (watcher, pending, failures)));
+                    }
+                    if failures > 0 {
+                        sleep(watcher.backoff(failures)).await;
+                    } else if watcher.primed {
+                        sleep(watcher.interval).await;
+                    }
+                    match watcher.poll().await {
+                        Ok(txns) => {
+                            failures = 0;
+                            pending.extend(txns);
+                        }
+                        Err(e) => {
+                            failures = failures.saturating_add(1);
+                            return Some((Err(e), (watcher, pending, failures)));
+                        }
+                    }
+                }
+            },
+        )
+    }
+}
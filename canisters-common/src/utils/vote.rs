@@ -1,10 +1,10 @@
 use candid::{CandidType, Principal};
-use hon_worker_common::{GameInfo, GameInfoReq, GameInfoReqV3, GameInfoReqV4};
+use hon_worker_common::{GameInfo, GameInfoReq, GameInfoReqV3, GameInfoReqV4, HonWorkerClient};
 use identity::{ic_agent::sign_message, msg_builder::Message, Signature};
 use serde::{Deserialize, Serialize};
 use web_time::Duration;
 
-use crate::{consts::CENTS_IN_E6S, Canisters, Error, HonError, Result};
+use crate::{consts::CENTS_IN_E6S, Canisters, HonError, Result};
 
 use super::time::current_epoch;
 
@@ -123,23 +123,41 @@ impl VoteDetails {
     }
 }
 
+pub async fn fetch_game_with_sats_info(
+    user_principal: Principal,
+    cloudflare_url: reqwest::Url,
+    request: GameInfoReq,
+) -> Result<Option<GameInfo>> {
+    let info = HonWorkerClient::new(cloudflare_url)
+        .game_info(user_principal, &request)
+        .await
+        .map_err(HonError::from)?;
+
+    Ok(info)
+}
+
 pub async fn fetch_game_with_sats_info_v3(
     user_principal: Principal,
     cloudflare_url: reqwest::Url,
     request: GameInfoReqV3,
 ) -> Result<Option<GameInfo>> {
-    let path = format!("/v3/game_info/{user_principal}");
-    let url = cloudflare_url.join(&path)?;
+    let info = HonWorkerClient::new(cloudflare_url)
+        .game_info_v3(user_principal, &request)
+        .await
+        .map_err(HonError::from)?;
 
-    let client = reqwest::Client::new();
-    let res = client.post(url).json(&request).send().await?;
-
-    if !res.status().is_success() {
-        let err = res.text().await?;
-        return Err(Error::Hon(HonError::Backend(err)));
-    }
+    Ok(info)
+}
 
-    let info = res.json().await?;
+pub async fn fetch_game_with_sats_info_v4(
+    user_principal: Principal,
+    cloudflare_url: reqwest::Url,
+    request: GameInfoReqV4,
+) -> Result<Option<GameInfo>> {
+    let info = HonWorkerClient::new(cloudflare_url)
+        .game_info_v4(user_principal, &request)
+        .await
+        .map_err(HonError::from)?;
 
     Ok(info)
 }
@@ -178,20 +196,7 @@ impl Canisters<true> {
         cloudflare_url: reqwest::Url,
         request: GameInfoReq,
     ) -> Result<Option<GameInfo>> {
-        let path = format!("/game_info/{}", self.user_principal());
-        let url = cloudflare_url.join(&path)?;
-
-        let client = reqwest::Client::new();
-        let res = client.post(url).json(&request).send().await?;
-
-        if !res.status().is_success() {
-            let err = res.text().await?;
-            return Err(Error::Hon(HonError::Backend(err)));
-        }
-
-        let info = res.json().await?;
-
-        Ok(info)
+        fetch_game_with_sats_info(self.user_principal(), cloudflare_url, request).await
     }
 
     pub async fn fetch_game_with_sats_info_v3(
@@ -207,19 +212,6 @@ impl Canisters<true> {
         cloudflare_url: reqwest::Url,
         request: GameInfoReqV4,
     ) -> Result<Option<GameInfo>> {
-        let path = format!("/v4/game_info/{}", self.user_principal());
-        let url = cloudflare_url.join(&path)?;
-
-        let client = reqwest::Client::new();
-        let res = client.post(url).json(&request).send().await?;
-
-        if !res.status().is_success() {
-            let err = res.text().await?;
-            return Err(Error::Hon(HonError::Backend(err)));
-        }
-
-        let info = res.json().await?;
-
-        Ok(info)
+        fetch_game_with_sats_info_v4(self.user_principal(), cloudflare_url, request).await
     }
 }
@@ -0,0 +1,245 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use candid::{Nat, Principal};
+use hon_worker_common::{
+    sign_referral_request_with_nonce, sign_vote_request_v4_with_nonce, GameInfo, GameInfoReq,
+    GameInfoReqV3, GameInfoReqV4, GameOutcome, HoNGameVoteReqV4, HonWorkerClient,
+    HonWorkerClientError, HotOrNot, ReferralReq, ReferralReqWithSignature, VoteRequestV4,
+    WorkerError,
+};
+use hon_worker_mock::MockHonWorker;
+use ic_agent::identity::AnonymousIdentity;
+use num_bigint::BigUint;
+use yral_canisters_common::{
+    cursored_data::{
+        ref_history::ReferralHistoryProvider,
+        vote::{VotesWithSatsProvider, VotesWithSatsProviderV2},
+        CursoredDataProvider,
+    },
+    utils::{
+        token::{types::SatsOperations, TokenOperations},
+        vote::{
+            fetch_game_with_sats_info, fetch_game_with_sats_info_v3, fetch_game_with_sats_info_v4,
+        },
+    },
+    Error, HonError,
+};
+
+static NEXT_NONCE: AtomicU64 = AtomicU64::new(0);
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id])
+}
+
+fn nonce() -> u64 {
+    NEXT_NONCE.fetch_add(1, Ordering::Relaxed)
+}
+
+fn worker_error<T>(res: Result<T, HonWorkerClientError>) -> WorkerError {
+    match res {
+        Err(HonWorkerClientError::Worker(e)) => e,
+        Err(e) => panic!("expected a worker error, got {e}"),
+        Ok(_) => panic!("expected a worker error"),
+    }
+}
+
+/// Vote 5 sats on `post_id` of `user(100)`, winning if `direction` is hot
+async fn vote(client: &HonWorkerClient, voter: Principal, post_id: &str, direction: HotOrNot) {
+    let request = VoteRequestV4 {
+        publisher_principal: user(100),
+        post_id: post_id.into(),
+        vote_amount: 5,
+        direction,
+    };
+    let nonce = nonce();
+    let signature =
+        sign_vote_request_v4_with_nonce(&AnonymousIdentity, request.clone(), nonce).unwrap();
+    let req = HoNGameVoteReqV4 {
+        request,
+        fetched_sentiment: HotOrNot::Hot,
+        post_creator: Some(user(100)),
+        signature,
+        nonce: Some(nonce),
+    };
+    client.vote_v4(voter, &req).await.unwrap();
+}
+
+fn referral(referrer: Principal, referee: Principal) -> ReferralReqWithSignature {
+    let request = ReferralReq {
+        referrer,
+        referee,
+        referee_canister: Principal::anonymous(),
+        amount: 5,
+    };
+    let nonce = nonce();
+    let signature =
+        sign_referral_request_with_nonce(&AnonymousIdentity, request.clone(), nonce).unwrap();
+    ReferralReqWithSignature {
+        request,
+        signature,
+        nonce: Some(nonce),
+    }
+}
+
+#[tokio::test]
+async fn sats_operations_update_worker_balance() {
+    let worker = MockHonWorker::start().await.unwrap();
+    worker.set_balance(user(1), 100u64);
+    let ops = SatsOperations::new(Some("test-jwt".into())).with_worker(worker.client());
+
+    ops.add_balance(user(1), 20).await.unwrap();
+    ops.deduct_balance(user(1), 50).await.unwrap();
+
+    assert_eq!(worker.balance(user(1)), BigUint::from(70u64));
+    assert_eq!(
+        ops.load_balance(user(1)).await.unwrap().e8s,
+        Nat::from(70u64)
+    );
+}
+
+#[tokio::test]
+async fn sats_operations_surface_worker_errors() {
+    let worker = MockHonWorker::start().await.unwrap();
+    worker.set_balance(user(1), 10u64);
+    let ops = SatsOperations::new(Some("test-jwt".into())).with_worker(worker.client());
+
+    let err = ops.deduct_balance(user(1), 50).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Hon(HonError::Client(HonWorkerClientError::Worker(
+                WorkerError::InsufficientFunds
+            )))
+        ),
+        "unexpected error: {err}"
+    );
+    assert_eq!(worker.balance(user(1)), BigUint::from(10u64));
+}
+
+#[tokio::test]
+async fn votes_with_sats_providers_page_games() {
+    let worker = MockHonWorker::start().await.unwrap();
+    let client = worker.client();
+    worker.set_balance(user(1), 100u64);
+    vote(&client, user(1), "1", HotOrNot::Hot).await;
+    vote(&client, user(1), "2", HotOrNot::Not).await;
+
+    let provider = VotesWithSatsProvider::new(user(1)).with_worker(worker.client());
+    let page = provider.get_by_cursor(0, 1).await.unwrap();
+    assert!(!page.end);
+    assert_eq!(page.data[0].post_id, "1");
+    assert_eq!(page.data[0].post_canister, Some(user(100)));
+    assert!(matches!(page.data[0].outcome, GameOutcome::Win { .. }));
+    let page = provider.get_by_cursor(1, 2).await.unwrap();
+    assert!(page.end);
+    assert_eq!(page.data[0].post_id, "2");
+    assert_eq!(
+        page.data[0].outcome,
+        GameOutcome::Loss {
+            lose_amt: BigUint::from(5u64)
+        }
+    );
+
+    let provider = VotesWithSatsProviderV2::new(user(1)).with_worker(worker.client());
+    let page = provider.get_by_cursor(0, 10).await.unwrap();
+    assert!(page.end);
+    assert_eq!(page.data.len(), 2);
+    assert_eq!(
+        page.data[1].updated_balance.clone(),
+        Some(worker.balance(user(1)))
+    );
+}
+
+#[tokio::test]
+async fn fetch_game_with_sats_info_finds_played_games() {
+    let worker = MockHonWorker::start().await.unwrap();
+    worker.set_balance(user(1), 100u64);
+    vote(&worker.client(), user(1), "7", HotOrNot::Hot).await;
+
+    let info = fetch_game_with_sats_info(
+        user(1),
+        worker.url(),
+        GameInfoReq {
+            post_canister: user(100),
+            post_id: 7,
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(info, Some(GameInfo::Vote { .. })));
+
+    let info = fetch_game_with_sats_info_v3(
+        user(1),
+        worker.url(),
+        GameInfoReqV3 {
+            publisher_principal: user(100),
+            post_id: 7,
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(info, Some(GameInfo::Vote { .. })));
+
+    let info = fetch_game_with_sats_info_v4(
+        user(1),
+        worker.url(),
+        GameInfoReqV4 {
+            publisher_principal: user(100),
+            post_id: "8".into(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(info.is_none());
+
+    let info = fetch_game_with_sats_info_v4(
+        user(100),
+        worker.url(),
+        GameInfoReqV4 {
+            publisher_principal: user(100),
+            post_id: "7".into(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(info, Some(GameInfo::CreatorReward(_))));
+}
+
+#[tokio::test]
+async fn referrals_credit_both_users_and_show_in_history() {
+    let worker = MockHonWorker::start().await.unwrap();
+    let client = worker.client();
+
+    client.referral(&referral(user(1), user(2))).await.unwrap();
+    client.referral(&referral(user(1), user(3))).await.unwrap();
+    assert_eq!(worker.balance(user(1)), BigUint::from(10u64));
+    assert_eq!(worker.balance(user(2)), BigUint::from(5u64));
+
+    assert_eq!(
+        worker_error(client.referral(&referral(user(4), user(2))).await),
+        WorkerError::AlreadyReferred
+    );
+
+    let provider = ReferralHistoryProvider::new(user(1)).with_worker(worker.client());
+    let page = provider.get_by_cursor(0, 1).await.unwrap();
+    assert!(!page.end);
+    assert_eq!(page.data[0].referee, user(2));
+    let page = provider.get_by_cursor(1, 2).await.unwrap();
+    assert!(page.end);
+    assert_eq!(page.data[0].referee, user(3));
+    assert_eq!(page.data[0].amount, 5);
+}
+
+#[tokio::test]
+async fn referrals_require_a_nonce() {
+    let worker = MockHonWorker::start().await.unwrap();
+    let client = worker.client();
+
+    let mut req = referral(user(1), user(2));
+    req.nonce = None;
+    assert_eq!(
+        worker_error(client.referral(&req).await),
+        WorkerError::MissingNonce
+    );
+    assert_eq!(worker.balance(user(1)), BigUint::ZERO);
+}
@@ -7,6 +7,7 @@ pub const CREATOR_COMMISSION_PERCENT: u64 = 10;
 pub const MIN_WITHDRAWAL_PER_TXN_SATS: u64 = 50;
 pub const MAX_WITHDRAWAL_PER_TXN_SATS: u64 = 60;
 pub const MAX_WITHDRAWAL_PER_DAY_SATS: u64 = 60;
+pub const MIN_WITHDRAWAL_PER_TXN_CENTS_E6S: u64 = 1_000_000; // 1 cent
 
 // Reward limit
 pub const NEW_USER_SIGNUP_REWARD_SATS: u64 = 25;
@@ -103,3 +104,4 @@ pub const LTX2_COST_USD_CENTS: u64 = 0; // Free - Self-hosted LTX-2 on Vast.ai H
 // Based on: $0.5 (50 cents) = 500 sats = 100 dolr
 pub const VIDEOGEN_USD_CENTS_TO_SATS: u64 = 10; // 1 cent = 10 sats
 pub const VIDEOGEN_USD_CENTS_TO_DOLR_E8S: u64 = 200_000_000; // 1 cent = 2 dolr = 2×10^8 e8s
+pub const VIDEOGEN_USD_CENTS_TO_USDC_E6S: u64 = 10_000; // 1 cent = 0.01 usdc = 10^4 e6s
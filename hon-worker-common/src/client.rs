@@ -0,0 +1,238 @@
+use candid::Principal;
+use reqwest::{Response, StatusCode, Url};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    GameInfo, GameInfoReq, GameInfoReqV3, GameInfoReqV4, HoNGameVoteReqV4, HoNGameWithdrawReq,
+    PaginatedGamesReq, PaginatedGamesRes, PaginatedGamesResV2, PaginatedGamesResV3,
+    PaginatedGamesResV4, PaginatedReferralsReq, PaginatedReferralsRes, ReferralReqWithSignature,
+    SatsBalanceInfo, SatsBalanceUpdateRequestV2, VerifiableClaimRequest, VoteResV2, WORKER_URL,
+    WorkerError, WorkerResponse,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum HonWorkerClientError {
+    #[error("network error when accessing worker: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("{0}")]
+    Worker(#[from] WorkerError),
+    #[error("unexpected response from worker ({status}): {body}")]
+    Unexpected { status: StatusCode, body: String },
+}
+
+pub type ClientResult<T> = Result<T, HonWorkerClientError>;
+
+/// Typed client for the hot-or-not worker
+#[derive(Clone, Debug)]
+pub struct HonWorkerClient {
+    client: reqwest::Client,
+    base_url: Url,
+    jwt_token: Option<String>,
+}
+
+impl Default for HonWorkerClient {
+    fn default() -> Self {
+        Self::new(WORKER_URL.parse().unwrap())
+    }
+}
+
+impl HonWorkerClient {
+    pub fn new(base_url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url,
+            jwt_token: None,
+        }
+    }
+
+    /// Authenticate requests with a JWT, required for server-only endpoints
+    pub fn with_jwt(mut self, jwt_token: String) -> Self {
+        self.jwt_token = Some(jwt_token);
+        self
+    }
+
+    fn url(&self, path: &str) -> Url {
+        self.base_url.join(path).expect("Url to be valid")
+    }
+
+    /// Decode either a plain `T` or a `WorkerResponse<T>`
+    async fn decode<T: DeserializeOwned>(res: Response) -> ClientResult<T> {
+        let status = res.status();
+        let body = res.text().await?;
+
+        if let Ok(res) = serde_json::from_str::<WorkerResponse<T>>(&body) {
+            return Ok(res?);
+        }
+        if status.is_success() {
+            if let Ok(res) = serde_json::from_str(&body) {
+                return Ok(res);
+            }
+        } else if let Some(err) = WorkerError::from_response_body(&body) {
+            return Err(err.into());
+        }
+
+        Err(HonWorkerClientError::Unexpected { status, body })
+    }
+
+    /// Decode the response of an endpoint that doesn't return anything on success
+    async fn decode_empty(res: Response) -> ClientResult<()> {
+        let status = res.status();
+        let body = res.text().await?;
+
+        if let Ok(res) = serde_json::from_str::<WorkerResponse<serde_json::Value>>(&body) {
+            return res.map(|_| ()).map_err(Into::into);
+        }
+        if status.is_success() {
+            return Ok(());
+        }
+        if let Some(err) = WorkerError::from_response_body(&body) {
+            return Err(err.into());
+        }
+
+        Err(HonWorkerClientError::Unexpected { status, body })
+    }
+
+    fn post_req(&self, path: &str, body: &impl Serialize) -> reqwest::RequestBuilder {
+        let req = self.client.post(self.url(path)).json(body);
+        match &self.jwt_token {
+            Some(jwt) => req.bearer_auth(jwt),
+            None => req,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> ClientResult<T> {
+        let mut req = self.client.get(self.url(path));
+        if let Some(jwt) = &self.jwt_token {
+            req = req.bearer_auth(jwt);
+        }
+        Self::decode(req.send().await?).await
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> ClientResult<T> {
+        Self::decode(self.post_req(path, body).send().await?).await
+    }
+
+    async fn post_empty(&self, path: &str, body: &impl Serialize) -> ClientResult<()> {
+        Self::decode_empty(self.post_req(path, body).send().await?).await
+    }
+
+    pub async fn balance(&self, user_principal: Principal) -> ClientResult<SatsBalanceInfo> {
+        self.balance_as(user_principal).await
+    }
+
+    /// `/balance/{owner}` decoded as `T`, for workers that share the route
+    /// but not the schema, e.g. the pump and dump worker
+    pub async fn balance_as<T: DeserializeOwned>(&self, owner: Principal) -> ClientResult<T> {
+        self.get(&format!("/balance/{owner}")).await
+    }
+
+    /// Compare-and-swap update of the user's balance, requires a JWT
+    /// fails with [`WorkerError::BalanceTransactionConflict`] if `previous_balance` is stale
+    pub async fn update_balance_v2(
+        &self,
+        user_principal: Principal,
+        req: &SatsBalanceUpdateRequestV2,
+    ) -> ClientResult<()> {
+        self.post_empty(&format!("/v2/update_balance/{user_principal}"), req)
+            .await
+    }
+
+    pub async fn vote_v4(
+        &self,
+        user_principal: Principal,
+        req: &HoNGameVoteReqV4,
+    ) -> ClientResult<VoteResV2> {
+        self.post(&format!("/v4/vote/{user_principal}"), req).await
+    }
+
+    pub async fn game_info(
+        &self,
+        user_principal: Principal,
+        req: &GameInfoReq,
+    ) -> ClientResult<Option<GameInfo>> {
+        self.post(&format!("/game_info/{user_principal}"), req)
+            .await
+    }
+
+    pub async fn game_info_v3(
+        &self,
+        user_principal: Principal,
+        req: &GameInfoReqV3,
+    ) -> ClientResult<Option<GameInfo>> {
+        self.post(&format!("/v3/game_info/{user_principal}"), req)
+            .await
+    }
+
+    pub async fn game_info_v4(
+        &self,
+        user_principal: Principal,
+        req: &GameInfoReqV4,
+    ) -> ClientResult<Option<GameInfo>> {
+        self.post(&format!("/v4/game_info/{user_principal}"), req)
+            .await
+    }
+
+    pub async fn games(
+        &self,
+        user_principal: Principal,
+        req: &PaginatedGamesReq,
+    ) -> ClientResult<PaginatedGamesRes> {
+        self.post(&format!("/games/{user_principal}"), req).await
+    }
+
+    /// Same endpoint as [`Self::games`], decoded with the v2 game schema
+    pub async fn games_v2(
+        &self,
+        user_principal: Principal,
+        req: &PaginatedGamesReq,
+    ) -> ClientResult<PaginatedGamesResV2> {
+        self.post(&format!("/games/{user_principal}"), req).await
+    }
+
+    pub async fn games_v3(
+        &self,
+        user_principal: Principal,
+        req: &PaginatedGamesReq,
+    ) -> ClientResult<PaginatedGamesResV3> {
+        self.post(&format!("/v3/games/{user_principal}"), req).await
+    }
+
+    pub async fn games_v4(
+        &self,
+        user_principal: Principal,
+        req: &PaginatedGamesReq,
+    ) -> ClientResult<PaginatedGamesResV4> {
+        self.post(&format!("/v4/games/{user_principal}"), req).await
+    }
+
+    pub async fn withdraw(
+        &self,
+        user_principal: Principal,
+        req: &HoNGameWithdrawReq,
+    ) -> ClientResult<()> {
+        self.post_empty(&format!("/withdraw/{user_principal}"), req)
+            .await
+    }
+
+    pub async fn referral(&self, req: &ReferralReqWithSignature) -> ClientResult<()> {
+        self.post_empty("/referral_reward", req).await
+    }
+
+    pub async fn referral_history(
+        &self,
+        user_principal: Principal,
+        req: &PaginatedReferralsReq,
+    ) -> ClientResult<PaginatedReferralsRes> {
+        self.post(&format!("/referral_history/{user_principal}"), req)
+            .await
+    }
+
+    /// Returns the amount of sats airdropped
+    pub async fn claim_airdrop(&self, req: &VerifiableClaimRequest) -> ClientResult<u64> {
+        self.post("/claim_airdrop", req).await
+    }
+}
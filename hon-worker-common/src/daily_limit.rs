@@ -0,0 +1,308 @@
+use std::{collections::HashMap, future::Future, ops::Range, sync::Mutex};
+
+use candid::Principal;
+use global_constants::{
+    AIRDROP_REWARD_PER_DAY_SATS, MAX_CREDITED_PER_DAY_PER_USER_SATS,
+    MAX_DEDUCTED_PER_DAY_PER_USER_SATS, SATS_AIRDROP_LIMIT_RANGE_SATS,
+};
+use serde::{Deserialize, Serialize};
+use web_time::Duration;
+
+use crate::WorkerError;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    Airdrop,
+    Credit,
+    Deduct,
+}
+
+impl LimitKind {
+    fn limit_reached(self) -> WorkerError {
+        match self {
+            Self::Airdrop => WorkerError::AirdropLimitReached,
+            Self::Credit => WorkerError::SatsCreditLimitReached,
+            Self::Deduct => WorkerError::SatsDeductLimitReached,
+        }
+    }
+}
+
+/// Index of the UTC day `now` (since the unix epoch) falls in
+pub fn day_index(now: Duration) -> u64 {
+    now.as_secs() / DAY.as_secs()
+}
+
+/// Per user, per [`LimitKind`] usage for each UTC day
+pub trait DailyUsageStore {
+    fn usage(
+        &self,
+        user: Principal,
+        kind: LimitKind,
+        day: u64,
+    ) -> impl Future<Output = Result<u64, WorkerError>>;
+
+    /// Add `amount` to the usage if the total stays within `limit`
+    /// returns the new total, or `None` if `amount` was rejected
+    ///
+    /// must be atomic, otherwise concurrent requests can exceed the limit
+    fn try_add(
+        &self,
+        user: Principal,
+        kind: LimitKind,
+        day: u64,
+        amount: u64,
+        limit: u64,
+    ) -> impl Future<Output = Result<Option<u64>, WorkerError>>;
+}
+
+/// [`DailyUsageStore`] keeping usage in memory, usage of past days is never evicted
+#[derive(Debug, Default)]
+pub struct InMemoryDailyUsage {
+    usage: Mutex<HashMap<(Principal, LimitKind, u64), u64>>,
+}
+
+impl DailyUsageStore for InMemoryDailyUsage {
+    async fn usage(&self, user: Principal, kind: LimitKind, day: u64) -> Result<u64, WorkerError> {
+        let usage = self.usage.lock().unwrap();
+        Ok(usage.get(&(user, kind, day)).copied().unwrap_or_default())
+    }
+
+    async fn try_add(
+        &self,
+        user: Principal,
+        kind: LimitKind,
+        day: u64,
+        amount: u64,
+        limit: u64,
+    ) -> Result<Option<u64>, WorkerError> {
+        let mut usage = self.usage.lock().unwrap();
+        let used = usage.entry((user, kind, day)).or_default();
+        match used.checked_add(amount) {
+            Some(total) if total <= limit => {
+                *used = total;
+                Ok(Some(total))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Daily limits for each [`LimitKind`], in sats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DailyLimits {
+    pub airdrop: u64,
+    pub credit: u64,
+    pub deduct: u64,
+}
+
+impl Default for DailyLimits {
+    fn default() -> Self {
+        Self {
+            airdrop: AIRDROP_REWARD_PER_DAY_SATS,
+            credit: MAX_CREDITED_PER_DAY_PER_USER_SATS,
+            deduct: MAX_DEDUCTED_PER_DAY_PER_USER_SATS,
+        }
+    }
+}
+
+impl DailyLimits {
+    pub fn get(&self, kind: LimitKind) -> u64 {
+        match kind {
+            LimitKind::Airdrop => self.airdrop,
+            LimitKind::Credit => self.credit,
+            LimitKind::Deduct => self.deduct,
+        }
+    }
+}
+
+/// Enforces [`DailyLimits`] per user over UTC days
+pub struct DailyLimiter<S> {
+    limits: DailyLimits,
+    airdrop_range: Range<u64>,
+    store: S,
+}
+
+impl<S: DailyUsageStore> DailyLimiter<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            limits: DailyLimits::default(),
+            airdrop_range: SATS_AIRDROP_LIMIT_RANGE_SATS,
+            store,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: DailyLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Amounts a single airdrop claim may be for
+    pub fn with_airdrop_range(mut self, airdrop_range: Range<u64>) -> Self {
+        self.airdrop_range = airdrop_range;
+        self
+    }
+
+    pub fn limits(&self) -> DailyLimits {
+        self.limits
+    }
+
+    /// Amount of `kind` `user` can still use today
+    /// `now` is the current time since the unix epoch
+    pub async fn remaining(
+        &self,
+        user: Principal,
+        kind: LimitKind,
+        now: Duration,
+    ) -> Result<u64, WorkerError> {
+        let used = self.store.usage(user, kind, day_index(now)).await?;
+        Ok(self.limits.get(kind).saturating_sub(used))
+    }
+
+    /// Use `amount` of today's `kind` limit, returning what's left
+    ///
+    /// fails with the [`WorkerError`] matching `kind` if the limit would be exceeded,
+    /// or with [`WorkerError::InvalidAirdropAmount`] for airdrops outside the allowed range,
+    /// in which case nothing is used
+    pub async fn consume(
+        &self,
+        user: Principal,
+        kind: LimitKind,
+        amount: u64,
+        now: Duration,
+    ) -> Result<u64, WorkerError> {
+        if kind == LimitKind::Airdrop && !self.airdrop_range.contains(&amount) {
+            return Err(WorkerError::InvalidAirdropAmount {
+                min: self.airdrop_range.start,
+                max: self.airdrop_range.end.saturating_sub(1),
+            });
+        }
+
+        let limit = self.limits.get(kind);
+        let total = self
+            .store
+            .try_add(user, kind, day_index(now), amount, limit)
+            .await?
+            .ok_or_else(|| kind.limit_reached())?;
+
+        Ok(limit - total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_on;
+
+    const LIMITS: DailyLimits = DailyLimits {
+        airdrop: 100,
+        credit: 50,
+        deduct: 20,
+    };
+
+    fn limiter() -> DailyLimiter<InMemoryDailyUsage> {
+        DailyLimiter::new(InMemoryDailyUsage::default())
+            .with_limits(LIMITS)
+            .with_airdrop_range(1..101)
+    }
+
+    fn user(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    // noon on some UTC day
+    const NOW: Duration = Duration::from_secs(20_000 * 24 * 60 * 60 + 12 * 60 * 60);
+
+    #[test]
+    fn consumes_up_to_the_limit() {
+        let limiter = limiter();
+
+        assert_eq!(
+            block_on(limiter.consume(user(1), LimitKind::Credit, 30, NOW)),
+            Ok(20)
+        );
+        assert_eq!(
+            block_on(limiter.consume(user(1), LimitKind::Credit, 20, NOW)),
+            Ok(0)
+        );
+        assert_eq!(
+            block_on(limiter.consume(user(1), LimitKind::Credit, 1, NOW)),
+            Err(WorkerError::SatsCreditLimitReached)
+        );
+    }
+
+    #[test]
+    fn rejected_amounts_are_not_used() {
+        let limiter = limiter();
+
+        assert_eq!(
+            block_on(limiter.consume(user(1), LimitKind::Deduct, 21, NOW)),
+            Err(WorkerError::SatsDeductLimitReached)
+        );
+        assert_eq!(
+            block_on(limiter.remaining(user(1), LimitKind::Deduct, NOW)),
+            Ok(20)
+        );
+    }
+
+    #[test]
+    fn limits_are_per_user_and_kind() {
+        let limiter = limiter();
+
+        block_on(limiter.consume(user(1), LimitKind::Airdrop, 100, NOW)).unwrap();
+        assert_eq!(
+            block_on(limiter.consume(user(1), LimitKind::Airdrop, 1, NOW)),
+            Err(WorkerError::AirdropLimitReached)
+        );
+        assert_eq!(
+            block_on(limiter.remaining(user(2), LimitKind::Airdrop, NOW)),
+            Ok(100)
+        );
+        assert_eq!(
+            block_on(limiter.remaining(user(1), LimitKind::Credit, NOW)),
+            Ok(50)
+        );
+    }
+
+    #[test]
+    fn limits_reset_at_utc_midnight() {
+        let limiter = limiter();
+
+        block_on(limiter.consume(user(1), LimitKind::Deduct, 20, NOW)).unwrap();
+        let before_midnight = NOW + Duration::from_secs(12 * 60 * 60 - 1);
+        assert_eq!(
+            block_on(limiter.remaining(user(1), LimitKind::Deduct, before_midnight)),
+            Ok(0)
+        );
+        let midnight = NOW + Duration::from_secs(12 * 60 * 60);
+        assert_eq!(
+            block_on(limiter.remaining(user(1), LimitKind::Deduct, midnight)),
+            Ok(20)
+        );
+    }
+
+    #[test]
+    fn airdrops_outside_the_range_are_rejected() {
+        let limiter = DailyLimiter::new(InMemoryDailyUsage::default());
+        let range = SATS_AIRDROP_LIMIT_RANGE_SATS;
+        let rejected = Err(WorkerError::InvalidAirdropAmount {
+            min: range.start,
+            max: range.end - 1,
+        });
+
+        assert_eq!(
+            block_on(limiter.consume(user(1), LimitKind::Airdrop, range.end, NOW)),
+            rejected
+        );
+        assert_eq!(
+            block_on(limiter.consume(user(1), LimitKind::Airdrop, range.start - 1, NOW)),
+            rejected
+        );
+        assert_eq!(
+            block_on(limiter.remaining(user(1), LimitKind::Airdrop, NOW)),
+            Ok(AIRDROP_REWARD_PER_DAY_SATS)
+        );
+        assert!(block_on(limiter.consume(user(1), LimitKind::Airdrop, range.start, NOW)).is_ok());
+    }
+}
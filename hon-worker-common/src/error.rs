@@ -2,7 +2,7 @@ use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Serialize, Deserialize, Debug, Error)]
+#[derive(Serialize, Deserialize, Debug, Error, PartialEq)]
 pub enum WorkerError {
     #[error("Invalid Signature")]
     InvalidSignature,
@@ -28,6 +28,76 @@ pub enum WorkerError {
     SatsCreditLimitReached,
     #[error("sats deduct limit reached")]
     SatsDeductLimitReached,
+    #[error("daily airdrop limit reached")]
+    AirdropLimitReached,
+    #[error("airdrop must be between {min} and {max}")]
+    InvalidAirdropAmount { min: u64, max: u64 },
+    #[error("request was already processed")]
+    ReplayedRequest,
+    #[error("request is missing a nonce")]
+    MissingNonce,
+    #[error("withdrawal must be at least {min}")]
+    WithdrawalBelowMinimum { min: u64 },
+    #[error("withdrawal must be at most {max}")]
+    WithdrawalAboveMaximum { max: u64 },
+    #[error("daily withdrawal limit reached, {remaining} left for today")]
+    WithdrawalDailyLimitReached { remaining: u128 },
+}
+
+impl WorkerError {
+    /// HTTP status code the worker responds with for this error
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::InvalidSignature => 401,
+            Self::InsufficientFunds => 402,
+            Self::PostNotFound => 404,
+            Self::AlreadyVotedOnPost
+            | Self::AlreadyReferred
+            | Self::BalanceTransactionConflict { .. }
+            | Self::ReplayedRequest => 409,
+            Self::InvalidAirdropDelta
+            | Self::InvalidAirdropAmount { .. }
+            | Self::MissingNonce
+            | Self::WithdrawalBelowMinimum { .. }
+            | Self::WithdrawalAboveMaximum { .. } => 400,
+            Self::TreasuryLimitReached
+            | Self::SatsCreditLimitReached
+            | Self::SatsDeductLimitReached
+            | Self::AirdropLimitReached
+            | Self::WithdrawalDailyLimitReached { .. } => 429,
+            Self::TreasuryOutOfFunds => 503,
+            Self::Internal(_) => 500,
+        }
+    }
+
+    /// Decode the body of an error response, either a [`WorkerErrorEnvelope`]
+    /// or a bare [`WorkerError`] from older worker versions
+    pub fn from_response_body(body: &str) -> Option<Self> {
+        if let Ok(envelope) = serde_json::from_str::<WorkerErrorEnvelope>(body) {
+            return Some(envelope.error);
+        }
+        serde_json::from_str(body).ok()
+    }
+}
+
+/// JSON body of the worker's error responses
+///
+/// `status` and `message` are informational, clients should match on `error`
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct WorkerErrorEnvelope {
+    pub status: u16,
+    pub message: String,
+    pub error: WorkerError,
+}
+
+impl From<WorkerError> for WorkerErrorEnvelope {
+    fn from(error: WorkerError) -> Self {
+        Self {
+            status: error.status_code(),
+            message: error.to_string(),
+            error,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Error)]
@@ -35,3 +105,66 @@ pub enum AirdropClaimError {
     #[error("Invalid Signature")]
     InvalidSignature,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_codes() {
+        let cases = [
+            (WorkerError::InvalidSignature, 401),
+            (WorkerError::InsufficientFunds, 402),
+            (WorkerError::PostNotFound, 404),
+            (WorkerError::AlreadyVotedOnPost, 409),
+            (WorkerError::ReplayedRequest, 409),
+            (
+                WorkerError::BalanceTransactionConflict {
+                    new_balance: BigUint::from(1u32),
+                },
+                409,
+            ),
+            (WorkerError::MissingNonce, 400),
+            (WorkerError::InvalidAirdropAmount { min: 25, max: 29 }, 400),
+            (WorkerError::WithdrawalBelowMinimum { min: 10 }, 400),
+            (WorkerError::TreasuryLimitReached, 429),
+            (
+                WorkerError::WithdrawalDailyLimitReached { remaining: 5 },
+                429,
+            ),
+            (WorkerError::TreasuryOutOfFunds, 503),
+            (WorkerError::Internal("oops".into()), 500),
+        ];
+        for (error, status) in cases {
+            assert_eq!(error.status_code(), status, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn envelope_round_trip() {
+        let error = WorkerError::WithdrawalAboveMaximum { max: 50 };
+        let envelope = WorkerErrorEnvelope::from(error);
+        assert_eq!(envelope.status, 400);
+        assert_eq!(envelope.message, "withdrawal must be at most 50");
+
+        let body = serde_json::to_string(&envelope).unwrap();
+        assert_eq!(
+            serde_json::from_str::<WorkerErrorEnvelope>(&body).unwrap(),
+            envelope
+        );
+        assert_eq!(
+            WorkerError::from_response_body(&body),
+            Some(WorkerError::WithdrawalAboveMaximum { max: 50 })
+        );
+    }
+
+    #[test]
+    fn decodes_bare_errors() {
+        let body = serde_json::to_string(&WorkerError::AlreadyReferred).unwrap();
+        assert_eq!(
+            WorkerError::from_response_body(&body),
+            Some(WorkerError::AlreadyReferred)
+        );
+        assert_eq!(WorkerError::from_response_body("Bad Gateway"), None);
+    }
+}
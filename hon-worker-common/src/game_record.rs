@@ -0,0 +1,153 @@
+use candid::Principal;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameInfo, GameInfoV2, GameRes, GameResV2, GameResV3, GameResV4, GameResV4WithCanister,
+    GameResult, GameResultV2,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GameOutcome {
+    CreatorReward(BigUint),
+    Win { win_amt: BigUint },
+    Loss { lose_amt: BigUint },
+}
+
+/// Version agnostic view of a game, convertible from every `GameRes*` version
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    /// Stringified for versions with numeric post ids
+    pub post_id: String,
+    /// Canister holding the post, not returned by the worker from v3 onwards
+    pub post_canister: Option<Principal>,
+    /// Only returned by the worker from v3 onwards
+    pub publisher_principal: Option<Principal>,
+    /// `None` for creator rewards
+    pub vote_amount: Option<BigUint>,
+    pub outcome: GameOutcome,
+    /// User's balance after the game, only known for v2
+    pub updated_balance: Option<BigUint>,
+}
+
+impl GameRecord {
+    fn new(post_id: String, game_info: impl Into<GameRecordInfo>) -> Self {
+        let GameRecordInfo {
+            vote_amount,
+            outcome,
+            updated_balance,
+        } = game_info.into();
+        Self {
+            post_id,
+            post_canister: None,
+            publisher_principal: None,
+            vote_amount,
+            outcome,
+            updated_balance,
+        }
+    }
+
+    pub fn with_post_canister(mut self, post_canister: Principal) -> Self {
+        self.post_canister = Some(post_canister);
+        self
+    }
+
+    pub fn with_publisher(mut self, publisher_principal: Principal) -> Self {
+        self.publisher_principal = Some(publisher_principal);
+        self
+    }
+}
+
+struct GameRecordInfo {
+    vote_amount: Option<BigUint>,
+    outcome: GameOutcome,
+    updated_balance: Option<BigUint>,
+}
+
+impl From<GameInfo> for GameRecordInfo {
+    fn from(value: GameInfo) -> Self {
+        match value {
+            GameInfo::CreatorReward(reward) => Self {
+                vote_amount: None,
+                outcome: GameOutcome::CreatorReward(reward),
+                updated_balance: None,
+            },
+            GameInfo::Vote {
+                vote_amount,
+                game_result,
+            } => Self {
+                vote_amount: Some(vote_amount),
+                outcome: match game_result {
+                    GameResult::Win { win_amt } => GameOutcome::Win { win_amt },
+                    GameResult::Loss { lose_amt } => GameOutcome::Loss { lose_amt },
+                },
+                updated_balance: None,
+            },
+        }
+    }
+}
+
+impl From<GameInfoV2> for GameRecordInfo {
+    fn from(value: GameInfoV2) -> Self {
+        match value {
+            GameInfoV2::CreatorReward(reward) => Self {
+                vote_amount: None,
+                outcome: GameOutcome::CreatorReward(reward),
+                updated_balance: None,
+            },
+            GameInfoV2::Vote {
+                vote_amount,
+                game_result,
+            } => {
+                let (outcome, updated_balance) = match game_result {
+                    GameResultV2::Win {
+                        win_amt,
+                        updated_balance,
+                    } => (GameOutcome::Win { win_amt }, updated_balance),
+                    GameResultV2::Loss {
+                        lose_amt,
+                        updated_balance,
+                    } => (GameOutcome::Loss { lose_amt }, updated_balance),
+                };
+                Self {
+                    vote_amount: Some(vote_amount),
+                    outcome,
+                    updated_balance: Some(updated_balance),
+                }
+            }
+        }
+    }
+}
+
+impl From<GameRes> for GameRecord {
+    fn from(value: GameRes) -> Self {
+        Self::new(value.post_id.to_string(), value.game_info)
+            .with_post_canister(value.post_canister)
+    }
+}
+
+impl From<GameResV2> for GameRecord {
+    fn from(value: GameResV2) -> Self {
+        Self::new(value.post_id.to_string(), value.game_info)
+            .with_post_canister(value.post_canister)
+    }
+}
+
+impl From<GameResV3> for GameRecord {
+    fn from(value: GameResV3) -> Self {
+        Self::new(value.post_id.to_string(), value.game_info)
+            .with_publisher(value.publisher_principal)
+    }
+}
+
+impl From<GameResV4> for GameRecord {
+    fn from(value: GameResV4) -> Self {
+        Self::new(value.post_id, value.game_info).with_publisher(value.publisher_principal)
+    }
+}
+
+impl From<GameResV4WithCanister> for GameRecord {
+    fn from(value: GameResV4WithCanister) -> Self {
+        Self::new(value.post_id, value.game_info).with_post_canister(value.post_creator_canister)
+    }
+}
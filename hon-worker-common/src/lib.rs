@@ -1,8 +1,46 @@
+#[cfg(feature = "client")]
+mod client;
+mod daily_limit;
 mod error;
+mod game_record;
+mod payout;
+mod replay;
+#[cfg(feature = "ic-git")]
+mod verify;
+mod withdrawal;
+mod withdrawal_policy;
 
+#[cfg(feature = "client")]
+pub use client::*;
+pub use daily_limit::*;
 pub use error::*;
+pub use game_record::*;
+pub use payout::*;
+pub use replay::*;
+#[cfg(feature = "ic-git")]
+pub use verify::*;
+pub use withdrawal::*;
+pub use withdrawal_policy::*;
+
+#[cfg(test)]
+mod test_utils {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    /// Run a future that never waits, e.g. one backed by an in-memory store
+    pub fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut cx = Context::from_waker(Waker::noop());
+        match pin!(fut).poll(&mut cx) {
+            Poll::Ready(out) => out,
+            Poll::Pending => panic!("future is not ready"),
+        }
+    }
+}
 
-use candid::{CandidType, Nat, Principal};
+use candid::{CandidType, Principal};
 use identity::{Signature, msg_builder::Message};
 use num_bigint::{BigInt, BigUint};
 use serde::{Deserialize, Serialize};
@@ -11,6 +49,11 @@ use serde_with::{DisplayFromStr, serde_as};
 pub const WORKER_URL: &str = "https://yral-hot-or-not.go-bazzinga.workers.dev/";
 pub type WorkerResponse<T> = Result<T, WorkerError>;
 
+/// Attach a single-use `nonce` to the signed envelope of `msg`, see [`ReplayGuard`]
+fn with_nonce(msg: Message, nonce: u64) -> Message {
+    msg.nonce(nonce.to_be_bytes().to_vec())
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, CandidType)]
 pub struct ClaimRequest {
     /// User's principal id
@@ -24,6 +67,12 @@ pub struct VerifiableClaimRequest {
     /// The amount of airdrop to be claimed in sats (e0s)
     pub amount: u64,
     pub signature: Signature,
+    /// Nonce the request was signed with
+    ///
+    /// optional on the wire for older clients, but the `verify_*` helpers
+    /// reject requests without one with [`WorkerError::MissingNonce`]
+    #[serde(default)]
+    pub nonce: Option<u64>,
 }
 
 pub fn verifiable_claim_request_message(args: ClaimRequest) -> Message {
@@ -33,6 +82,10 @@ pub fn verifiable_claim_request_message(args: ClaimRequest) -> Message {
         .expect("Request must serialize")
 }
 
+pub fn verifiable_claim_request_message_with_nonce(args: ClaimRequest, nonce: u64) -> Message {
+    with_nonce(verifiable_claim_request_message(args), nonce)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SatsBalanceInfo {
     pub balance: BigUint,
@@ -206,6 +259,18 @@ pub fn sign_claim_request(
     sign_message(sender, msg)
 }
 
+/// Sign a single-use airdrop claim, see [`ReplayGuard`]
+#[cfg(feature = "client")]
+pub fn sign_claim_request_with_nonce(
+    sender: &impl ic_agent::Identity,
+    request: ClaimRequest,
+    nonce: u64,
+) -> identity::Result<Signature> {
+    use identity::ic_agent::sign_message;
+    let msg = verifiable_claim_request_message_with_nonce(request, nonce);
+    sign_message(sender, msg)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct WithdrawRequest {
     pub receiver: Principal,
@@ -216,6 +281,12 @@ pub struct WithdrawRequest {
 pub struct HoNGameWithdrawReq {
     pub request: WithdrawRequest,
     pub signature: Signature,
+    /// Nonce the request was signed with
+    ///
+    /// optional on the wire for older clients, but the `verify_*` helpers
+    /// reject requests without one with [`WorkerError::MissingNonce`]
+    #[serde(default)]
+    pub nonce: Option<u64>,
 }
 
 pub fn hon_game_withdraw_msg(request: &WithdrawRequest) -> identity::msg_builder::Message {
@@ -225,6 +296,13 @@ pub fn hon_game_withdraw_msg(request: &WithdrawRequest) -> identity::msg_builder
         .expect("Withdraw request should serialize")
 }
 
+pub fn hon_game_withdraw_msg_with_nonce(
+    request: &WithdrawRequest,
+    nonce: u64,
+) -> identity::msg_builder::Message {
+    with_nonce(hon_game_withdraw_msg(request), nonce)
+}
+
 #[cfg(feature = "client")]
 pub fn sign_withdraw_request(
     sender: &impl ic_agent::Identity,
@@ -235,6 +313,18 @@ pub fn sign_withdraw_request(
     sign_message(sender, msg)
 }
 
+/// Sign a single-use withdraw request, see [`ReplayGuard`]
+#[cfg(feature = "client")]
+pub fn sign_withdraw_request_with_nonce(
+    sender: &impl ic_agent::Identity,
+    request: WithdrawRequest,
+    nonce: u64,
+) -> identity::Result<Signature> {
+    use identity::ic_agent::sign_message;
+    let msg = hon_game_withdraw_msg_with_nonce(&request, nonce);
+    sign_message(sender, msg)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ReferralItem {
     pub referrer: Principal,
@@ -247,6 +337,12 @@ pub struct ReferralItem {
 pub struct ReferralReqWithSignature {
     pub request: ReferralReq,
     pub signature: Signature,
+    /// Nonce the request was signed with
+    ///
+    /// optional on the wire for older clients, but the `verify_*` helpers
+    /// reject requests without one with [`WorkerError::MissingNonce`]
+    #[serde(default)]
+    pub nonce: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, CandidType)]
@@ -269,6 +365,13 @@ pub fn hon_referral_msg(request: ReferralReq) -> identity::msg_builder::Message
         .expect("Referral request should serialize")
 }
 
+pub fn hon_referral_msg_with_nonce(
+    request: ReferralReq,
+    nonce: u64,
+) -> identity::msg_builder::Message {
+    with_nonce(hon_referral_msg(request), nonce)
+}
+
 #[cfg(feature = "client")]
 pub fn sign_referral_request(
     sender: &impl ic_agent::Identity,
@@ -279,6 +382,18 @@ pub fn sign_referral_request(
     sign_message(sender, msg)
 }
 
+/// Sign a single-use referral request, see [`ReplayGuard`]
+#[cfg(feature = "client")]
+pub fn sign_referral_request_with_nonce(
+    sender: &impl ic_agent::Identity,
+    request: ReferralReq,
+    nonce: u64,
+) -> identity::Result<Signature> {
+    use identity::ic_agent::sign_message;
+    let msg = hon_referral_msg_with_nonce(request, nonce);
+    sign_message(sender, msg)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PaginatedReferralsReq {
     pub cursor: Option<u64>,
@@ -291,12 +406,6 @@ pub struct PaginatedReferralsRes {
     pub cursor: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub enum WithdrawalState {
-    Value(Nat),
-    NeedMoreEarnings(Nat),
-}
-
 #[serde_as]
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SatsBalanceUpdateRequest {
@@ -389,6 +498,19 @@ pub fn sign_vote_request_v4(
     sign_message(sender, msg)
 }
 
+/// Sign a single-use vote request, see [`ReplayGuard`]
+#[cfg(feature = "client")]
+pub fn sign_vote_request_v4_with_nonce(
+    sender: &impl ic_agent::Identity,
+    request: VoteRequestV4,
+    nonce: u64,
+) -> identity::Result<Signature> {
+    use identity::ic_agent::sign_message;
+
+    let msg = hon_game_vote_msg_v4_with_nonce(request, nonce);
+    sign_message(sender, msg)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct HoNGameVoteReqV3 {
     pub request: VoteRequestV3,
@@ -404,6 +526,12 @@ pub struct HoNGameVoteReqV4 {
     pub fetched_sentiment: HotOrNot,
     pub post_creator: Option<Principal>,
     pub signature: Signature,
+    /// Nonce the request was signed with
+    ///
+    /// optional on the wire for older clients, but the `verify_*` helpers
+    /// reject requests without one with [`WorkerError::MissingNonce`]
+    #[serde(default)]
+    pub nonce: Option<u64>,
 }
 
 pub fn hon_game_vote_msg_v3(request: VoteRequestV3) -> identity::msg_builder::Message {
@@ -420,6 +548,13 @@ pub fn hon_game_vote_msg_v4(request: VoteRequestV4) -> identity::msg_builder::Me
         .expect("Vote request should serialize")
 }
 
+pub fn hon_game_vote_msg_v4_with_nonce(
+    request: VoteRequestV4,
+    nonce: u64,
+) -> identity::msg_builder::Message {
+    with_nonce(hon_game_vote_msg_v4(request), nonce)
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct VoteRequestWithSentiment {
     pub request: VoteRequest,
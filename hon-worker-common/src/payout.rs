@@ -0,0 +1,179 @@
+//! Deterministic hot-or-not payout rules, shared by workers and clients
+//!
+//! - the creator of the post earns [`CREATOR_COMMISSION_PERCENT`] of every vote, rounded down
+//! - a vote matching the post's sentiment wins the vote amount minus the creator's commission
+//! - any other vote loses the vote amount
+//!
+//! the commission comes from `global-constants`, the win/loss rule is the one `hon-worker-mock`
+//! settles games with. The deployed worker's settlement code doesn't live in this repository,
+//! so the [`GameResult`](crate::GameResult) it returns stays authoritative over these estimates
+
+use global_constants::{CREATOR_COMMISSION_PERCENT, CoinState, MAX_BET_AMOUNT_SATS};
+use num_bigint::BigUint;
+use thiserror::Error;
+
+use crate::{GameOutcome, HotOrNot};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PayoutError {
+    #[error("vote amount must be greater than 0")]
+    ZeroVote,
+    #[error("vote amount {0} exceeds the maximum bet of {MAX_BET_AMOUNT_SATS}")]
+    AboveMaxBet(u64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Payout {
+    pub vote_amount: u64,
+    /// Amount won by the voter, 0 on a loss
+    pub win_amt: u64,
+    /// Amount lost by the voter, 0 on a win
+    pub lose_amt: u64,
+    pub creator_reward: u64,
+}
+
+impl Payout {
+    pub fn is_win(&self) -> bool {
+        self.lose_amt == 0
+    }
+
+    /// Change of the voter's balance
+    pub fn balance_delta(&self) -> i128 {
+        self.win_amt as i128 - self.lose_amt as i128
+    }
+
+    /// Outcome as reported by the worker for the voter
+    pub fn outcome(&self) -> GameOutcome {
+        if self.is_win() {
+            GameOutcome::Win {
+                win_amt: BigUint::from(self.win_amt),
+            }
+        } else {
+            GameOutcome::Loss {
+                lose_amt: BigUint::from(self.lose_amt),
+            }
+        }
+    }
+}
+
+pub fn creator_reward(vote_amount: u64) -> u64 {
+    vote_amount * CREATOR_COMMISSION_PERCENT / 100
+}
+
+fn validate_vote_amount(vote_amount: u64) -> Result<(), PayoutError> {
+    if vote_amount == 0 {
+        return Err(PayoutError::ZeroVote);
+    }
+    if vote_amount > MAX_BET_AMOUNT_SATS {
+        return Err(PayoutError::AboveMaxBet(vote_amount));
+    }
+    Ok(())
+}
+
+/// Payout for a vote of `vote_amount` sats in `direction` on a post with `sentiment`
+pub fn compute_payout(
+    vote_amount: u64,
+    direction: HotOrNot,
+    sentiment: HotOrNot,
+) -> Result<Payout, PayoutError> {
+    validate_vote_amount(vote_amount)?;
+
+    Ok(settle(vote_amount, direction, sentiment))
+}
+
+fn settle(vote_amount: u64, direction: HotOrNot, sentiment: HotOrNot) -> Payout {
+    let creator_reward = creator_reward(vote_amount);
+    let (win_amt, lose_amt) = if direction == sentiment {
+        (vote_amount - creator_reward, 0)
+    } else {
+        (0, vote_amount)
+    };
+
+    Payout {
+        vote_amount,
+        win_amt,
+        lose_amt,
+        creator_reward,
+    }
+}
+
+/// Possible payouts for a vote before the sentiment is known, as `(win, loss)`
+pub fn expected_payouts(vote_amount: u64) -> Result<(Payout, Payout), PayoutError> {
+    Ok((
+        compute_payout(vote_amount, HotOrNot::Hot, HotOrNot::Hot)?,
+        compute_payout(vote_amount, HotOrNot::Hot, HotOrNot::Not)?,
+    ))
+}
+
+/// [`expected_payouts`] for a vote with `coin`, amounts in sats
+///
+/// despite its name, [`CoinState::to_cents`] is the coin's value in sats,
+/// e.g. [`MAX_BET_AMOUNT_SATS`] is the value of [`CoinState::C5`]
+pub fn expected_payouts_for_coin(coin: CoinState) -> Result<(Payout, Payout), PayoutError> {
+    expected_payouts(coin.to_cents())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_sentiment_wins_minus_commission() {
+        let payout = compute_payout(5, HotOrNot::Not, HotOrNot::Not).unwrap();
+        assert!(payout.is_win());
+        assert_eq!(payout.creator_reward, 5 * CREATOR_COMMISSION_PERCENT / 100);
+        assert_eq!(payout.win_amt, 5 - payout.creator_reward);
+        assert_eq!(payout.balance_delta(), payout.win_amt as i128);
+    }
+
+    #[test]
+    fn commission_is_taken_from_wins() {
+        assert_eq!(creator_reward(100), 10);
+
+        let payout = settle(100, HotOrNot::Hot, HotOrNot::Hot);
+        assert_eq!(payout.creator_reward, 10);
+        assert_eq!(payout.win_amt, 90);
+        assert_eq!(payout.lose_amt, 0);
+
+        let payout = settle(100, HotOrNot::Hot, HotOrNot::Not);
+        assert_eq!(payout.creator_reward, 10);
+        assert_eq!(payout.win_amt, 0);
+        assert_eq!(payout.lose_amt, 100);
+    }
+
+    #[test]
+    fn opposite_sentiment_loses_vote() {
+        let payout = compute_payout(5, HotOrNot::Hot, HotOrNot::Not).unwrap();
+        assert!(!payout.is_win());
+        assert_eq!(payout.win_amt, 0);
+        assert_eq!(payout.lose_amt, 5);
+        assert_eq!(payout.balance_delta(), -5);
+        assert_eq!(
+            payout.outcome(),
+            GameOutcome::Loss {
+                lose_amt: BigUint::from(5u64)
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_vote_amounts() {
+        assert_eq!(
+            compute_payout(0, HotOrNot::Hot, HotOrNot::Hot),
+            Err(PayoutError::ZeroVote)
+        );
+        assert_eq!(
+            compute_payout(MAX_BET_AMOUNT_SATS + 1, HotOrNot::Hot, HotOrNot::Hot),
+            Err(PayoutError::AboveMaxBet(MAX_BET_AMOUNT_SATS + 1))
+        );
+    }
+
+    #[test]
+    fn expected_payouts_cover_both_outcomes() {
+        let (win, loss) = expected_payouts_for_coin(CoinState::C5).unwrap();
+        assert!(win.is_win());
+        assert!(!loss.is_win());
+        assert_eq!(win.vote_amount, MAX_BET_AMOUNT_SATS);
+        assert_eq!(win.creator_reward, loss.creator_reward);
+    }
+}
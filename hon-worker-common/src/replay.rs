@@ -0,0 +1,114 @@
+use std::{collections::HashMap, future::Future, sync::Mutex};
+
+use candid::Principal;
+use web_time::{Duration, Instant};
+
+use crate::WorkerError;
+
+/// The IC verifier accepts signatures until their ingress expiry, which can be up to
+/// the max ingress ttl (5 minutes) plus the permitted drift (60 seconds) in the future
+///
+/// nonces are kept for that long plus another drift of margin, so a request can't be
+/// replayed once its nonce is forgotten
+pub const DEFAULT_REPLAY_TTL: Duration = Duration::from_secs(5 * 60 + 2 * 60);
+
+/// Tracks nonces of signed requests so a captured request can't be submitted twice
+pub trait ReplayGuard {
+    /// Record `nonce` for `sender`
+    /// fails with [`WorkerError::ReplayedRequest`] if it was already used
+    fn check_and_record(
+        &self,
+        sender: Principal,
+        nonce: u64,
+    ) -> impl Future<Output = Result<(), WorkerError>>;
+}
+
+/// [`ReplayGuard`] that keeps nonces in memory until their ttl passes
+///
+/// only suitable for a single worker instance
+#[derive(Debug)]
+pub struct InMemoryReplayGuard {
+    ttl: Duration,
+    seen: Mutex<HashMap<(Principal, u64), Instant>>,
+}
+
+impl Default for InMemoryReplayGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_TTL)
+    }
+}
+
+impl InMemoryReplayGuard {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: Mutex::default(),
+        }
+    }
+
+    fn check_and_record_at(
+        &self,
+        sender: Principal,
+        nonce: u64,
+        now: Instant,
+    ) -> Result<(), WorkerError> {
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, expires_at| *expires_at > now);
+
+        if seen.contains_key(&(sender, nonce)) {
+            return Err(WorkerError::ReplayedRequest);
+        }
+        seen.insert((sender, nonce), now + self.ttl);
+
+        Ok(())
+    }
+}
+
+impl ReplayGuard for InMemoryReplayGuard {
+    async fn check_and_record(&self, sender: Principal, nonce: u64) -> Result<(), WorkerError> {
+        self.check_and_record_at(sender, nonce, Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: u8) -> Principal {
+        Principal::from_slice(&[id])
+    }
+
+    #[test]
+    fn rejects_reused_nonce() {
+        let guard = InMemoryReplayGuard::default();
+        let now = Instant::now();
+
+        assert!(guard.check_and_record_at(user(1), 7, now).is_ok());
+        assert!(matches!(
+            guard.check_and_record_at(user(1), 7, now),
+            Err(WorkerError::ReplayedRequest)
+        ));
+    }
+
+    #[test]
+    fn nonces_are_per_sender() {
+        let guard = InMemoryReplayGuard::default();
+        let now = Instant::now();
+
+        assert!(guard.check_and_record_at(user(1), 7, now).is_ok());
+        assert!(guard.check_and_record_at(user(2), 7, now).is_ok());
+    }
+
+    #[test]
+    fn nonce_can_be_reused_after_ttl() {
+        let guard = InMemoryReplayGuard::new(Duration::from_secs(10));
+        let now = Instant::now();
+
+        assert!(guard.check_and_record_at(user(1), 7, now).is_ok());
+        assert!(
+            guard
+                .check_and_record_at(user(1), 7, now + Duration::from_secs(11))
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Server-side counterparts of the `sign_*` helpers
+//!
+//! every function rebuilds the signed [`Message`](identity::msg_builder::Message)
+//! and checks that it was signed by `sender`
+
+use candid::Principal;
+use identity::Signature;
+
+use crate::{
+    ClaimRequest, ReferralReq, VoteRequestV4, WithdrawRequest, WorkerError,
+    hon_game_vote_msg_v4_with_nonce, hon_game_withdraw_msg_with_nonce, hon_referral_msg_with_nonce,
+    verifiable_claim_request_message_with_nonce,
+};
+
+fn verify_signature(
+    sender: Principal,
+    signature: Signature,
+    msg: identity::msg_builder::Message,
+) -> Result<(), WorkerError> {
+    signature
+        .verify_identity(sender, msg)
+        .map_err(|_| WorkerError::InvalidSignature)
+}
+
+fn require_nonce(nonce: Option<u64>) -> Result<u64, WorkerError> {
+    nonce.ok_or(WorkerError::MissingNonce)
+}
+
+/// `nonce` is the one sent with the request, requests without one are rejected
+/// with [`WorkerError::MissingNonce`] as they could be replayed
+///
+/// this only checks the signature, use a [`ReplayGuard`](crate::ReplayGuard)
+/// to reject reused nonces
+pub fn verify_vote_request_v4(
+    sender: Principal,
+    request: VoteRequestV4,
+    nonce: Option<u64>,
+    signature: Signature,
+) -> Result<(), WorkerError> {
+    verify_signature(
+        sender,
+        signature,
+        hon_game_vote_msg_v4_with_nonce(request, require_nonce(nonce)?),
+    )
+}
+
+/// see [`verify_vote_request_v4`]
+pub fn verify_withdraw_request(
+    sender: Principal,
+    request: &WithdrawRequest,
+    nonce: Option<u64>,
+    signature: Signature,
+) -> Result<(), WorkerError> {
+    verify_signature(
+        sender,
+        signature,
+        hon_game_withdraw_msg_with_nonce(request, require_nonce(nonce)?),
+    )
+}
+
+/// see [`verify_vote_request_v4`]
+pub fn verify_referral_request(
+    sender: Principal,
+    request: ReferralReq,
+    nonce: Option<u64>,
+    signature: Signature,
+) -> Result<(), WorkerError> {
+    verify_signature(
+        sender,
+        signature,
+        hon_referral_msg_with_nonce(request, require_nonce(nonce)?),
+    )
+}
+
+/// see [`verify_vote_request_v4`]
+pub fn verify_claim_request(
+    sender: Principal,
+    request: ClaimRequest,
+    nonce: Option<u64>,
+    signature: Signature,
+) -> Result<(), WorkerError> {
+    verify_signature(
+        sender,
+        signature,
+        verifiable_claim_request_message_with_nonce(request, require_nonce(nonce)?),
+    )
+}
+
+/// signing goes through the dev-dependencies, so these only need `ic-git`:
+/// `cargo test -p hon-worker-common --features ic-git`
+#[cfg(test)]
+mod tests {
+    use ic_agent::identity::{Identity, Secp256k1Identity};
+    use identity::ic_agent::sign_message;
+    use rand::rngs::OsRng;
+    use web_time::Duration;
+
+    use super::*;
+    use crate::HotOrNot;
+
+    fn sign_vote_request_v4_with_nonce(
+        identity: &impl Identity,
+        request: VoteRequestV4,
+        nonce: u64,
+    ) -> identity::Result<Signature> {
+        sign_message(identity, hon_game_vote_msg_v4_with_nonce(request, nonce))
+    }
+
+    fn identity() -> Secp256k1Identity {
+        Secp256k1Identity::from_private_key(k256::SecretKey::random(&mut OsRng))
+    }
+
+    fn vote_request() -> VoteRequestV4 {
+        VoteRequestV4 {
+            publisher_principal: Principal::anonymous(),
+            post_id: "1".into(),
+            vote_amount: 10,
+            direction: HotOrNot::Hot,
+        }
+    }
+
+    #[test]
+    fn accepts_valid_signature() {
+        let identity = identity();
+        let sender = identity.sender().unwrap();
+        let signature = sign_vote_request_v4_with_nonce(&identity, vote_request(), 7).unwrap();
+
+        assert_eq!(
+            verify_vote_request_v4(sender, vote_request(), Some(7), signature),
+            Ok(())
+        );
+
+        let claim = ClaimRequest {
+            user_principal: sender,
+        };
+        let msg = verifiable_claim_request_message_with_nonce(claim.clone(), 3);
+        let signature = sign_message(&identity, msg).unwrap();
+        assert_eq!(
+            verify_claim_request(sender, claim.clone(), Some(3), signature.clone()),
+            Ok(())
+        );
+        assert_eq!(
+            verify_claim_request(sender, claim, None, signature),
+            Err(WorkerError::MissingNonce)
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_principal() {
+        let signature = sign_vote_request_v4_with_nonce(&identity(), vote_request(), 7).unwrap();
+        let other = identity().sender().unwrap();
+
+        assert_eq!(
+            verify_vote_request_v4(other, vote_request(), Some(7), signature),
+            Err(WorkerError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_request() {
+        let identity = identity();
+        let sender = identity.sender().unwrap();
+        let signature = sign_vote_request_v4_with_nonce(&identity, vote_request(), 7).unwrap();
+
+        let tampered = VoteRequestV4 {
+            vote_amount: 1000,
+            ..vote_request()
+        };
+        assert_eq!(
+            verify_vote_request_v4(sender, tampered, Some(7), signature.clone()),
+            Err(WorkerError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_vote_request_v4(sender, vote_request(), Some(8), signature.clone()),
+            Err(WorkerError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_vote_request_v4(sender, vote_request(), None, signature),
+            Err(WorkerError::MissingNonce)
+        );
+    }
+
+    #[test]
+    fn rejects_expired_message() {
+        let identity = identity();
+        let sender = identity.sender().unwrap();
+        let msg =
+            hon_game_vote_msg_v4_with_nonce(vote_request(), 7).ingress_max_age(Duration::ZERO);
+        let signature = sign_message(&identity, msg).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        assert_eq!(
+            verify_vote_request_v4(sender, vote_request(), Some(7), signature),
+            Err(WorkerError::InvalidSignature)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use candid::Nat;
+use global_constants::{
+    MAX_WITHDRAWAL_PER_TXN_SATS, MIN_WITHDRAWAL_PER_TXN_CENTS_E6S, MIN_WITHDRAWAL_PER_TXN_SATS,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum WithdrawalState {
+    /// Amount that can be withdrawn right now
+    Value(Nat),
+    /// Earnings required before anything can be withdrawn
+    NeedMoreEarnings(Nat),
+}
+
+/// Per transaction withdrawal limits, in the token's base unit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawalLimits {
+    pub min_per_txn: u64,
+    pub max_per_txn: Option<u64>,
+}
+
+impl WithdrawalLimits {
+    pub const SATS: Self = Self {
+        min_per_txn: MIN_WITHDRAWAL_PER_TXN_SATS,
+        max_per_txn: Some(MAX_WITHDRAWAL_PER_TXN_SATS),
+    };
+
+    pub const CENTS: Self = Self {
+        min_per_txn: MIN_WITHDRAWAL_PER_TXN_CENTS_E6S,
+        max_per_txn: None,
+    };
+}
+
+impl WithdrawalState {
+    /// Withdrawal state of a user given their balance, the airdrops included in it,
+    /// and the amount the worker allows them to withdraw
+    ///
+    /// airdropped tokens are never withdrawable, so a user whose balance doesn't exceed
+    /// their airdrops by `min_per_txn` needs to earn the difference
+    pub fn compute(
+        balance: &Nat,
+        net_airdrop_reward: &Nat,
+        withdrawable: &Nat,
+        limits: WithdrawalLimits,
+    ) -> Self {
+        let min = Nat::from(limits.min_per_txn);
+        if *withdrawable >= min {
+            let value = match limits.max_per_txn {
+                Some(max) if *withdrawable > Nat::from(max) => Nat::from(max),
+                _ => withdrawable.clone(),
+            };
+            return Self::Value(value);
+        }
+
+        let short_of_min = min.clone() - withdrawable.clone();
+        let required_balance = net_airdrop_reward.clone() + min;
+        let short_of_airdrop = if required_balance > *balance {
+            required_balance - balance.clone()
+        } else {
+            Nat::from(0u32)
+        };
+
+        Self::NeedMoreEarnings(short_of_min.max(short_of_airdrop))
+    }
+
+    pub fn can_withdraw(&self) -> bool {
+        matches!(self, Self::Value(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: WithdrawalLimits = WithdrawalLimits {
+        min_per_txn: 100,
+        max_per_txn: Some(1000),
+    };
+
+    fn state(balance: u64, airdrop: u64, withdrawable: u64) -> WithdrawalState {
+        WithdrawalState::compute(
+            &balance.into(),
+            &airdrop.into(),
+            &withdrawable.into(),
+            LIMITS,
+        )
+    }
+
+    #[test]
+    fn withdrawable_above_min_is_available() {
+        assert_eq!(state(500, 0, 500), WithdrawalState::Value(500u64.into()));
+    }
+
+    #[test]
+    fn withdrawable_exactly_min_is_available() {
+        assert_eq!(state(100, 0, 100), WithdrawalState::Value(100u64.into()));
+    }
+
+    #[test]
+    fn withdrawable_is_capped_at_max() {
+        assert_eq!(state(5000, 0, 5000), WithdrawalState::Value(1000u64.into()));
+    }
+
+    #[test]
+    fn no_max_means_no_cap() {
+        let state = WithdrawalState::compute(
+            &10_000_000u64.into(),
+            &0u64.into(),
+            &10_000_000u64.into(),
+            WithdrawalLimits::CENTS,
+        );
+        assert_eq!(state, WithdrawalState::Value(10_000_000u64.into()));
+    }
+
+    #[test]
+    fn balance_below_airdrop_needs_airdrop_plus_min() {
+        assert_eq!(
+            state(300, 500, 0),
+            WithdrawalState::NeedMoreEarnings(300u64.into())
+        );
+    }
+
+    #[test]
+    fn balance_equal_to_airdrop_needs_min() {
+        assert_eq!(
+            state(500, 500, 0),
+            WithdrawalState::NeedMoreEarnings(100u64.into())
+        );
+    }
+
+    #[test]
+    fn balance_above_airdrop_does_not_underflow() {
+        assert_eq!(
+            state(700, 500, 0),
+            WithdrawalState::NeedMoreEarnings(100u64.into())
+        );
+    }
+
+    #[test]
+    fn partial_earnings_need_the_rest_of_min() {
+        assert_eq!(
+            state(560, 500, 60),
+            WithdrawalState::NeedMoreEarnings(40u64.into())
+        );
+    }
+
+    #[test]
+    fn empty_balance_needs_min() {
+        assert_eq!(
+            state(0, 0, 0),
+            WithdrawalState::NeedMoreEarnings(100u64.into())
+        );
+    }
+}
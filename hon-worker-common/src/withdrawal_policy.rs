@@ -0,0 +1,307 @@
+use std::{collections::HashMap, future::Future, sync::Mutex};
+
+use candid::Principal;
+use global_constants::MAX_WITHDRAWAL_PER_DAY_SATS;
+use thiserror::Error;
+use web_time::Duration;
+
+use crate::{WithdrawRequest, WithdrawalLimits, WorkerError};
+
+/// Length of the rolling window for daily withdrawal limits
+pub const WITHDRAWAL_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Record of past withdrawals, used to enforce rolling window limits
+///
+/// timestamps are durations since the unix epoch
+pub trait WithdrawalUsageStore {
+    /// Total amount withdrawn by `user` at or after `since`
+    fn withdrawn_since(
+        &self,
+        user: Principal,
+        since: Duration,
+    ) -> impl Future<Output = Result<u128, WorkerError>>;
+
+    /// Record a withdrawal of `amount` at `at` if the total withdrawn in the
+    /// [`WITHDRAWAL_WINDOW`] ending at `at` stays within `limit`
+    /// returns the new total, or `None` if `amount` was rejected
+    ///
+    /// must be atomic, otherwise concurrent requests can exceed the limit
+    fn try_record_withdrawal(
+        &self,
+        user: Principal,
+        amount: u128,
+        at: Duration,
+        limit: u128,
+    ) -> impl Future<Output = Result<Option<u128>, WorkerError>>;
+}
+
+/// [`WithdrawalUsageStore`] keeping the last [`WITHDRAWAL_WINDOW`] of withdrawals in memory
+#[derive(Debug, Default)]
+pub struct InMemoryWithdrawalUsage {
+    withdrawals: Mutex<HashMap<Principal, Vec<(Duration, u128)>>>,
+}
+
+impl WithdrawalUsageStore for InMemoryWithdrawalUsage {
+    async fn withdrawn_since(&self, user: Principal, since: Duration) -> Result<u128, WorkerError> {
+        let withdrawals = self.withdrawals.lock().unwrap();
+        Ok(withdrawals
+            .get(&user)
+            .into_iter()
+            .flatten()
+            .filter(|(at, _)| *at >= since)
+            .map(|(_, amount)| amount)
+            .sum())
+    }
+
+    async fn try_record_withdrawal(
+        &self,
+        user: Principal,
+        amount: u128,
+        at: Duration,
+        limit: u128,
+    ) -> Result<Option<u128>, WorkerError> {
+        let mut withdrawals = self.withdrawals.lock().unwrap();
+        let entries = withdrawals.entry(user).or_default();
+        let cutoff = at.saturating_sub(WITHDRAWAL_WINDOW);
+        entries.retain(|(recorded_at, _)| *recorded_at >= cutoff);
+
+        let withdrawn: u128 = entries.iter().map(|(_, amount)| amount).sum();
+        match withdrawn.checked_add(amount) {
+            Some(total) if total <= limit => {
+                entries.push((at, amount));
+                Ok(Some(total))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum WithdrawalRejection {
+    #[error("withdrawal must be at least {min}")]
+    BelowMinimum { min: u64 },
+    #[error("withdrawal must be at most {max}")]
+    AboveMaximum { max: u64 },
+    #[error("daily withdrawal limit reached, {remaining} left for today")]
+    DailyLimitReached { remaining: u128 },
+    #[error("{0}")]
+    Store(#[from] WorkerError),
+}
+
+impl From<WithdrawalRejection> for WorkerError {
+    fn from(value: WithdrawalRejection) -> Self {
+        match value {
+            WithdrawalRejection::BelowMinimum { min } => Self::WithdrawalBelowMinimum { min },
+            WithdrawalRejection::AboveMaximum { max } => Self::WithdrawalAboveMaximum { max },
+            WithdrawalRejection::DailyLimitReached { remaining } => {
+                Self::WithdrawalDailyLimitReached { remaining }
+            }
+            WithdrawalRejection::Store(e) => e,
+        }
+    }
+}
+
+/// Validates withdrawals against per transaction and rolling [`WITHDRAWAL_WINDOW`] limits
+pub struct WithdrawalPolicy<S> {
+    limits: WithdrawalLimits,
+    max_per_window: Option<u64>,
+    store: S,
+}
+
+impl<S: WithdrawalUsageStore> WithdrawalPolicy<S> {
+    /// Policy with the SATS limits from `global_constants`
+    pub fn new(store: S) -> Self {
+        Self {
+            limits: WithdrawalLimits::SATS,
+            max_per_window: Some(MAX_WITHDRAWAL_PER_DAY_SATS),
+            store,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: WithdrawalLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// `None` disables the daily limit
+    pub fn with_max_per_day(mut self, max_per_day: Option<u64>) -> Self {
+        self.max_per_window = max_per_day;
+        self
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    fn validate_per_txn(&self, req: &WithdrawRequest) -> Result<(), WithdrawalRejection> {
+        if req.amount < self.limits.min_per_txn as u128 {
+            return Err(WithdrawalRejection::BelowMinimum {
+                min: self.limits.min_per_txn,
+            });
+        }
+        match self.limits.max_per_txn {
+            Some(max) if req.amount > max as u128 => {
+                return Err(WithdrawalRejection::AboveMaximum { max });
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    async fn remaining(
+        &self,
+        user: Principal,
+        max_per_window: u64,
+        now: Duration,
+    ) -> Result<u128, WithdrawalRejection> {
+        let withdrawn = self
+            .store
+            .withdrawn_since(user, now.saturating_sub(WITHDRAWAL_WINDOW))
+            .await?;
+        Ok((max_per_window as u128).saturating_sub(withdrawn))
+    }
+
+    /// Check `req` against the limits without recording it
+    /// `now` is the current time since the unix epoch
+    pub async fn validate(
+        &self,
+        user: Principal,
+        req: &WithdrawRequest,
+        now: Duration,
+    ) -> Result<(), WithdrawalRejection> {
+        self.validate_per_txn(req)?;
+
+        let Some(max_per_window) = self.max_per_window else {
+            return Ok(());
+        };
+        let remaining = self.remaining(user, max_per_window, now).await?;
+        if req.amount > remaining {
+            return Err(WithdrawalRejection::DailyLimitReached { remaining });
+        }
+
+        Ok(())
+    }
+
+    /// [`Self::validate`] and record the withdrawal if it's allowed
+    ///
+    /// the daily limit is checked and recorded atomically by the store
+    pub async fn validate_and_record(
+        &self,
+        user: Principal,
+        req: &WithdrawRequest,
+        now: Duration,
+    ) -> Result<(), WithdrawalRejection> {
+        self.validate_per_txn(req)?;
+
+        let limit = self.max_per_window.map_or(u128::MAX, u128::from);
+        let recorded = self
+            .store
+            .try_record_withdrawal(user, req.amount, now, limit)
+            .await?;
+        if recorded.is_some() {
+            return Ok(());
+        }
+
+        let remaining = match self.max_per_window {
+            Some(max_per_window) => self.remaining(user, max_per_window, now).await?,
+            None => 0,
+        };
+        Err(WithdrawalRejection::DailyLimitReached { remaining })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_on;
+
+    const LIMITS: WithdrawalLimits = WithdrawalLimits {
+        min_per_txn: 10,
+        max_per_txn: Some(50),
+    };
+
+    fn policy() -> WithdrawalPolicy<InMemoryWithdrawalUsage> {
+        WithdrawalPolicy::new(InMemoryWithdrawalUsage::default())
+            .with_limits(LIMITS)
+            .with_max_per_day(Some(80))
+    }
+
+    fn req(amount: u128) -> WithdrawRequest {
+        WithdrawRequest {
+            receiver: Principal::anonymous(),
+            amount,
+        }
+    }
+
+    fn user() -> Principal {
+        Principal::from_slice(&[1])
+    }
+
+    #[test]
+    fn rejects_amounts_outside_per_txn_limits() {
+        let policy = policy();
+        let now = Duration::from_secs(1_000_000);
+
+        assert_eq!(
+            block_on(policy.validate(user(), &req(9), now)),
+            Err(WithdrawalRejection::BelowMinimum { min: 10 })
+        );
+        assert_eq!(
+            block_on(policy.validate(user(), &req(51), now)),
+            Err(WithdrawalRejection::AboveMaximum { max: 50 })
+        );
+        assert_eq!(block_on(policy.validate(user(), &req(50), now)), Ok(()));
+    }
+
+    #[test]
+    fn enforces_rolling_daily_limit() {
+        let policy = policy();
+        let now = Duration::from_secs(1_000_000);
+
+        block_on(policy.validate_and_record(user(), &req(50), now)).unwrap();
+        assert_eq!(
+            block_on(policy.validate_and_record(user(), &req(40), now + Duration::from_secs(60))),
+            Err(WithdrawalRejection::DailyLimitReached { remaining: 30 })
+        );
+        block_on(policy.validate_and_record(user(), &req(30), now + Duration::from_secs(60)))
+            .unwrap();
+
+        let next_day = now + WITHDRAWAL_WINDOW + Duration::from_secs(1);
+        assert_eq!(
+            block_on(policy.validate(user(), &req(50), next_day)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejections_are_not_recorded() {
+        let policy = policy();
+        let now = Duration::from_secs(1_000_000);
+
+        assert!(block_on(policy.validate_and_record(user(), &req(51), now)).is_err());
+        assert_eq!(
+            block_on(policy.store().withdrawn_since(user(), Duration::ZERO)),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn store_checks_and_records_atomically() {
+        let store = InMemoryWithdrawalUsage::default();
+        let now = Duration::from_secs(1_000_000);
+
+        assert_eq!(
+            block_on(store.try_record_withdrawal(user(), 50, now, 80)),
+            Ok(Some(50))
+        );
+        assert_eq!(
+            block_on(store.try_record_withdrawal(user(), 40, now, 80)),
+            Ok(None)
+        );
+        assert_eq!(
+            block_on(store.withdrawn_since(user(), Duration::ZERO)),
+            Ok(50)
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! In-process stand-in for the hot-or-not worker at [`hon_worker_common::WORKER_URL`]
+//!
+//! serves the balance, vote, game, withdraw and referral endpoints of [`HonWorkerClient`]
+//! over in-memory state, so those flows can be tested offline
+//!
+//! signatures are not verified, the user is taken from the request path (the referee for
+//! referrals). Nonces are still required and can only be used once
+mod routes;
+mod state;
+
+use std::{io, net::SocketAddr, sync::Arc};
+
+use candid::Principal;
+use hon_worker_common::{GameResV4, HoNGameVoteReqV4, HonWorkerClient, VoteResV2, WorkerError};
+use num_bigint::BigUint;
+use tokio::{net::TcpListener, task::JoinHandle};
+use url::Url;
+
+use state::MockState;
+
+/// Hot-or-not worker listening on a random local port, stopped when dropped
+pub struct MockHonWorker {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    server: JoinHandle<()>,
+}
+
+impl MockHonWorker {
+    /// Must be called from within a tokio runtime
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(MockState::default());
+
+        let app = routes::router(state.clone());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app)
+                .await
+                .expect("mock worker failed");
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            server,
+        })
+    }
+
+    pub fn url(&self) -> Url {
+        format!("http://{}/", self.addr).parse().unwrap()
+    }
+
+    pub fn client(&self) -> HonWorkerClient {
+        HonWorkerClient::new(self.url())
+    }
+
+    pub fn set_balance(&self, user: Principal, balance: impl Into<BigUint>) {
+        self.state.set_balance(user, balance.into());
+    }
+
+    pub fn balance(&self, user: Principal) -> BigUint {
+        self.state.balance(user).balance
+    }
+
+    /// Games played by `user`, oldest first
+    pub fn games(&self, user: Principal) -> Vec<GameResV4> {
+        self.state.games(user)
+    }
+
+    /// Play a v4 game for `user` directly against the worker's state, like
+    /// [`HonWorkerClient::vote_v4`] without going through HTTP
+    pub fn vote_v4(
+        &self,
+        user: Principal,
+        req: HoNGameVoteReqV4,
+    ) -> Result<VoteResV2, WorkerError> {
+        self.state.vote_v4(user, req)
+    }
+}
+
+impl Drop for MockHonWorker {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
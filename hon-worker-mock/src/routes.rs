@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use candid::Principal;
+use hon_worker_common::{
+    GameInfo, GameInfoReq, GameInfoReqV3, GameInfoReqV4, HoNGameVoteReqV4, HoNGameWithdrawReq,
+    PaginatedGamesReq, PaginatedGamesResV2, PaginatedGamesResV3, PaginatedGamesResV4,
+    PaginatedReferralsReq, PaginatedReferralsRes, ReferralReqWithSignature, SatsBalanceInfo,
+    SatsBalanceUpdateRequestV2, VoteResV2, WorkerError, WorkerErrorEnvelope,
+};
+
+use crate::state::MockState;
+
+type AppState = State<Arc<MockState>>;
+
+/// [`WorkerError`] sent as a [`WorkerErrorEnvelope`], like the real worker
+struct ApiError(WorkerError);
+
+impl From<WorkerError> for ApiError {
+    fn from(value: WorkerError) -> Self {
+        Self(value)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status =
+            StatusCode::from_u16(self.0.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(WorkerErrorEnvelope::from(self.0))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+fn principal(text: &str) -> ApiResult<Principal> {
+    Principal::from_text(text)
+        .map_err(|e| WorkerError::Internal(format!("invalid principal: {e}")).into())
+}
+
+pub(crate) fn router(state: Arc<MockState>) -> Router {
+    Router::new()
+        .route("/balance/{user}", get(balance))
+        .route("/v2/update_balance/{user}", post(update_balance_v2))
+        .route("/v4/vote/{user}", post(vote_v4))
+        .route("/game_info/{user}", post(game_info))
+        .route("/v3/game_info/{user}", post(game_info_v3))
+        .route("/v4/game_info/{user}", post(game_info_v4))
+        .route("/games/{user}", post(games_v2))
+        .route("/v3/games/{user}", post(games_v3))
+        .route("/v4/games/{user}", post(games_v4))
+        .route("/withdraw/{user}", post(withdraw))
+        .route("/referral_reward", post(referral))
+        .route("/referral_history/{user}", post(referral_history))
+        .with_state(state)
+}
+
+async fn balance(
+    State(state): AppState,
+    Path(user): Path<String>,
+) -> ApiResult<Json<SatsBalanceInfo>> {
+    Ok(Json(state.balance(principal(&user)?)))
+}
+
+async fn update_balance_v2(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<SatsBalanceUpdateRequestV2>,
+) -> ApiResult<StatusCode> {
+    state.update_balance_v2(principal(&user)?, req)?;
+    Ok(StatusCode::OK)
+}
+
+async fn vote_v4(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<HoNGameVoteReqV4>,
+) -> ApiResult<Json<VoteResV2>> {
+    Ok(Json(state.vote_v4(principal(&user)?, req)?))
+}
+
+async fn game_info(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<GameInfoReq>,
+) -> ApiResult<Json<Option<GameInfo>>> {
+    Ok(Json(state.game_info(principal(&user)?, req)))
+}
+
+async fn game_info_v3(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<GameInfoReqV3>,
+) -> ApiResult<Json<Option<GameInfo>>> {
+    Ok(Json(state.game_info_v3(principal(&user)?, req)))
+}
+
+async fn game_info_v4(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<GameInfoReqV4>,
+) -> ApiResult<Json<Option<GameInfo>>> {
+    Ok(Json(state.game_info_v4(principal(&user)?, req)))
+}
+
+async fn games_v2(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<PaginatedGamesReq>,
+) -> ApiResult<Json<PaginatedGamesResV2>> {
+    Ok(Json(state.games_v2(principal(&user)?, req)?))
+}
+
+async fn games_v3(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<PaginatedGamesReq>,
+) -> ApiResult<Json<PaginatedGamesResV3>> {
+    Ok(Json(state.games_v3(principal(&user)?, req)?))
+}
+
+async fn games_v4(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<PaginatedGamesReq>,
+) -> ApiResult<Json<PaginatedGamesResV4>> {
+    Ok(Json(state.games_v4(principal(&user)?, req)?))
+}
+
+async fn withdraw(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<HoNGameWithdrawReq>,
+) -> ApiResult<StatusCode> {
+    state.withdraw(principal(&user)?, req).await?;
+    Ok(StatusCode::OK)
+}
+
+async fn referral(
+    State(state): AppState,
+    Json(req): Json<ReferralReqWithSignature>,
+) -> ApiResult<StatusCode> {
+    state.referral(req)?;
+    Ok(StatusCode::OK)
+}
+
+async fn referral_history(
+    State(state): AppState,
+    Path(user): Path<String>,
+    Json(req): Json<PaginatedReferralsReq>,
+) -> ApiResult<Json<PaginatedReferralsRes>> {
+    Ok(Json(state.referral_history(principal(&user)?, req)))
+}
@@ -0,0 +1,414 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use candid::Principal;
+use hon_worker_common::{
+    GameInfo, GameInfoReq, GameInfoReqV3, GameInfoReqV4, GameInfoV2, GameResV2, GameResV3,
+    GameResV4, GameResult, GameResultV2, HoNGameVoteReqV4, HoNGameWithdrawReq,
+    InMemoryWithdrawalUsage, PaginatedGamesReq, PaginatedGamesResV2, PaginatedGamesResV3,
+    PaginatedGamesResV4, PaginatedReferralsReq, PaginatedReferralsRes, ReferralItem,
+    ReferralReqWithSignature, SatsBalanceInfo, SatsBalanceUpdateRequestV2, VoteResV2,
+    WithdrawalPolicy, WorkerError, compute_payout,
+};
+use num_bigint::{BigInt, BigUint};
+
+fn internal(msg: impl ToString) -> WorkerError {
+    WorkerError::Internal(msg.to_string())
+}
+
+fn now() -> std::time::Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time to be after the unix epoch")
+}
+
+/// A game as stored by the mock, served in the shape of every API version
+///
+/// v1 and v2 have no publisher, the publisher's principal stands in for the post canister.
+/// Versions with numeric post ids only list games whose id is a number
+#[derive(Clone)]
+struct Game {
+    publisher_principal: Principal,
+    post_id: String,
+    game_info: GameInfoV2,
+}
+
+impl Game {
+    fn numeric_post_id(&self) -> Option<u64> {
+        self.post_id.parse().ok()
+    }
+
+    fn game_info(&self) -> GameInfo {
+        match &self.game_info {
+            GameInfoV2::CreatorReward(reward) => GameInfo::CreatorReward(reward.clone()),
+            GameInfoV2::Vote {
+                vote_amount,
+                game_result,
+            } => GameInfo::Vote {
+                vote_amount: vote_amount.clone(),
+                game_result: match game_result {
+                    GameResultV2::Win { win_amt, .. } => GameResult::Win {
+                        win_amt: win_amt.clone(),
+                    },
+                    GameResultV2::Loss { lose_amt, .. } => GameResult::Loss {
+                        lose_amt: lose_amt.clone(),
+                    },
+                },
+            },
+        }
+    }
+
+    fn v2(&self) -> Option<GameResV2> {
+        Some(GameResV2 {
+            post_canister: self.publisher_principal,
+            post_id: self.numeric_post_id()?,
+            game_info: self.game_info.clone(),
+        })
+    }
+
+    fn v3(&self) -> Option<GameResV3> {
+        Some(GameResV3 {
+            publisher_principal: self.publisher_principal,
+            post_id: self.numeric_post_id()?,
+            game_info: self.game_info(),
+        })
+    }
+
+    fn v4(&self) -> GameResV4 {
+        GameResV4 {
+            publisher_principal: self.publisher_principal,
+            post_id: self.post_id.clone(),
+            game_info: self.game_info(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    balances: HashMap<Principal, SatsBalanceInfo>,
+    games: HashMap<Principal, Vec<Game>>,
+    /// referrals made by each referrer, oldest first
+    referrals: HashMap<Principal, Vec<ReferralItem>>,
+    referred: HashSet<Principal>,
+    nonces: HashSet<(Principal, u64)>,
+}
+
+impl Inner {
+    fn account(&mut self, user: Principal) -> &mut SatsBalanceInfo {
+        self.balances
+            .entry(user)
+            .or_insert_with(|| SatsBalanceInfo {
+                balance: BigUint::ZERO,
+                airdropped: BigUint::ZERO,
+            })
+    }
+
+    fn credit(&mut self, user: Principal, amount: impl Into<BigUint>) {
+        self.account(user).balance += amount.into();
+    }
+
+    fn deduct(&mut self, user: Principal, amount: impl Into<BigUint>) -> Result<(), WorkerError> {
+        let amount = amount.into();
+        let account = self.account(user);
+        if account.balance < amount {
+            return Err(WorkerError::InsufficientFunds);
+        }
+        account.balance -= amount;
+        Ok(())
+    }
+
+    /// Nonces never expire in the mock
+    fn use_nonce(&mut self, sender: Principal, nonce: Option<u64>) -> Result<(), WorkerError> {
+        let nonce = nonce.ok_or(WorkerError::MissingNonce)?;
+        if !self.nonces.insert((sender, nonce)) {
+            return Err(WorkerError::ReplayedRequest);
+        }
+        Ok(())
+    }
+
+    fn games(&self, user: Principal) -> &[Game] {
+        self.games.get(&user).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// State of the mock worker
+///
+/// the lock is never held across an await point
+pub(crate) struct MockState {
+    inner: Mutex<Inner>,
+    withdrawals: WithdrawalPolicy<InMemoryWithdrawalUsage>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::default(),
+            withdrawals: WithdrawalPolicy::new(InMemoryWithdrawalUsage::default()),
+        }
+    }
+}
+
+/// Page of `items` starting at `offset`, with the offset of the next page if any
+fn paginate<T: Clone>(items: &[T], offset: usize, page_size: usize) -> (Vec<T>, Option<usize>) {
+    let start = offset.min(items.len());
+    let end = start.saturating_add(page_size).min(items.len());
+    let next = (end < items.len()).then_some(end);
+    (items[start..end].to_vec(), next)
+}
+
+/// Page of `user`'s games in the shape returned by `version`
+fn games_page<T: Clone>(
+    games: &[Game],
+    req: PaginatedGamesReq,
+    version: impl Fn(&Game) -> Option<T>,
+) -> Result<(Vec<T>, Option<String>), WorkerError> {
+    let offset = req
+        .cursor
+        .map(|cursor| cursor.parse::<usize>())
+        .transpose()
+        .map_err(internal)?
+        .unwrap_or_default();
+
+    let games: Vec<_> = games.iter().filter_map(version).collect();
+    let (games, next) = paginate(&games, offset, req.page_size);
+
+    Ok((games, next.map(|next| next.to_string())))
+}
+
+impl MockState {
+    pub fn set_balance(&self, user: Principal, balance: BigUint) {
+        self.inner.lock().unwrap().account(user).balance = balance;
+    }
+
+    pub fn balance(&self, user: Principal) -> SatsBalanceInfo {
+        self.inner.lock().unwrap().account(user).clone()
+    }
+
+    pub fn games(&self, user: Principal) -> Vec<GameResV4> {
+        let inner = self.inner.lock().unwrap();
+        inner.games(user).iter().map(Game::v4).collect()
+    }
+
+    pub fn update_balance_v2(
+        &self,
+        user: Principal,
+        req: SatsBalanceUpdateRequestV2,
+    ) -> Result<(), WorkerError> {
+        if req.is_airdropped && req.delta < BigInt::ZERO {
+            return Err(WorkerError::InvalidAirdropDelta);
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let account = inner.account(user);
+        if account.balance != req.previous_balance {
+            return Err(WorkerError::BalanceTransactionConflict {
+                new_balance: account.balance.clone(),
+            });
+        }
+
+        let new_balance = (BigInt::from(account.balance.clone()) + &req.delta)
+            .to_biguint()
+            .ok_or(WorkerError::InsufficientFunds)?;
+        account.balance = new_balance;
+        if req.is_airdropped {
+            account.airdropped += req.delta.magnitude();
+        }
+
+        Ok(())
+    }
+
+    pub fn vote_v4(
+        &self,
+        user: Principal,
+        req: HoNGameVoteReqV4,
+    ) -> Result<VoteResV2, WorkerError> {
+        let request = req.request;
+        let vote_amount = u64::try_from(request.vote_amount).map_err(internal)?;
+        let payout = compute_payout(vote_amount, request.direction, req.fetched_sentiment)
+            .map_err(internal)?;
+
+        let mut inner = self.inner.lock().unwrap();
+        let already_voted = inner.games(user).iter().any(|game| {
+            game.publisher_principal == request.publisher_principal
+                && game.post_id == request.post_id
+                && matches!(game.game_info, GameInfoV2::Vote { .. })
+        });
+        if already_voted {
+            return Err(WorkerError::AlreadyVotedOnPost);
+        }
+        if inner.account(user).balance < BigUint::from(vote_amount) {
+            return Err(WorkerError::InsufficientFunds);
+        }
+        inner.use_nonce(user, req.nonce)?;
+
+        inner.deduct(user, payout.lose_amt)?;
+        inner.credit(user, payout.win_amt);
+        let updated_balance = inner.account(user).balance.clone();
+
+        let game_result = if payout.is_win() {
+            GameResultV2::Win {
+                win_amt: BigUint::from(payout.win_amt),
+                updated_balance,
+            }
+        } else {
+            GameResultV2::Loss {
+                lose_amt: BigUint::from(payout.lose_amt),
+                updated_balance,
+            }
+        };
+        inner.games.entry(user).or_default().push(Game {
+            publisher_principal: request.publisher_principal,
+            post_id: request.post_id.clone(),
+            game_info: GameInfoV2::Vote {
+                vote_amount: BigUint::from(vote_amount),
+                game_result: game_result.clone(),
+            },
+        });
+
+        if let Some(creator) = req.post_creator {
+            inner.credit(creator, payout.creator_reward);
+            inner.games.entry(creator).or_default().push(Game {
+                publisher_principal: request.publisher_principal,
+                post_id: request.post_id,
+                game_info: GameInfoV2::CreatorReward(BigUint::from(payout.creator_reward)),
+            });
+        }
+
+        Ok(VoteResV2 { game_result })
+    }
+
+    fn find_game(&self, user: Principal, matches: impl Fn(&Game) -> bool) -> Option<GameInfo> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .games(user)
+            .iter()
+            .find(|game| matches(game))
+            .map(Game::game_info)
+    }
+
+    pub fn game_info(&self, user: Principal, req: GameInfoReq) -> Option<GameInfo> {
+        self.find_game(user, |game| {
+            game.publisher_principal == req.post_canister
+                && game.numeric_post_id() == Some(req.post_id)
+        })
+    }
+
+    pub fn game_info_v3(&self, user: Principal, req: GameInfoReqV3) -> Option<GameInfo> {
+        self.find_game(user, |game| {
+            game.publisher_principal == req.publisher_principal
+                && game.numeric_post_id() == Some(req.post_id)
+        })
+    }
+
+    pub fn game_info_v4(&self, user: Principal, req: GameInfoReqV4) -> Option<GameInfo> {
+        self.find_game(user, |game| {
+            game.publisher_principal == req.publisher_principal && game.post_id == req.post_id
+        })
+    }
+
+    /// Served for both v1 and v2, v1 clients ignore the updated balances
+    pub fn games_v2(
+        &self,
+        user: Principal,
+        req: PaginatedGamesReq,
+    ) -> Result<PaginatedGamesResV2, WorkerError> {
+        let inner = self.inner.lock().unwrap();
+        let (games, next) = games_page(inner.games(user), req, Game::v2)?;
+        Ok(PaginatedGamesResV2 { games, next })
+    }
+
+    pub fn games_v3(
+        &self,
+        user: Principal,
+        req: PaginatedGamesReq,
+    ) -> Result<PaginatedGamesResV3, WorkerError> {
+        let inner = self.inner.lock().unwrap();
+        let (games, next) = games_page(inner.games(user), req, Game::v3)?;
+        Ok(PaginatedGamesResV3 { games, next })
+    }
+
+    pub fn games_v4(
+        &self,
+        user: Principal,
+        req: PaginatedGamesReq,
+    ) -> Result<PaginatedGamesResV4, WorkerError> {
+        let inner = self.inner.lock().unwrap();
+        let (games, next) = games_page(inner.games(user), req, |game| Some(game.v4()))?;
+        Ok(PaginatedGamesResV4 { games, next })
+    }
+
+    /// Pays out the withdrawal by deducting it from the balance,
+    /// within the SATS limits of [`WithdrawalPolicy`]
+    pub async fn withdraw(
+        &self,
+        user: Principal,
+        req: HoNGameWithdrawReq,
+    ) -> Result<(), WorkerError> {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.account(user).balance < BigUint::from(req.request.amount) {
+                return Err(WorkerError::InsufficientFunds);
+            }
+            inner.use_nonce(user, req.nonce)?;
+        }
+
+        self.withdrawals
+            .validate_and_record(user, &req.request, now())
+            .await?;
+
+        self.inner.lock().unwrap().deduct(user, req.request.amount)
+    }
+
+    /// Credits both sides of the referral, signed by the referee
+    pub fn referral(&self, req: ReferralReqWithSignature) -> Result<(), WorkerError> {
+        let request = req.request;
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.referred.contains(&request.referee) {
+            return Err(WorkerError::AlreadyReferred);
+        }
+        inner.use_nonce(request.referee, req.nonce)?;
+
+        inner.referred.insert(request.referee);
+        inner.credit(request.referrer, request.amount);
+        inner.credit(request.referee, request.amount);
+        inner
+            .referrals
+            .entry(request.referrer)
+            .or_default()
+            .push(ReferralItem {
+                referrer: request.referrer,
+                referee: request.referee,
+                amount: request.amount,
+                created_at: now().as_secs(),
+            });
+
+        Ok(())
+    }
+
+    pub fn referral_history(
+        &self,
+        user: Principal,
+        req: PaginatedReferralsReq,
+    ) -> PaginatedReferralsRes {
+        let inner = self.inner.lock().unwrap();
+        let referrals = inner
+            .referrals
+            .get(&user)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let (items, next) = paginate(
+            referrals,
+            req.cursor.unwrap_or_default() as usize,
+            req.limit as usize,
+        );
+
+        PaginatedReferralsRes {
+            items,
+            cursor: next.map(|next| next as u64),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use candid::Principal;
+use hon_worker_common::{
+    GameInfo, GameInfoReqV4, GameResultV2, HoNGameVoteReqV4, HoNGameWithdrawReq,
+    HonWorkerClientError, HotOrNot, PaginatedGamesReq, SatsBalanceUpdateRequestV2, VoteRequestV4,
+    WithdrawRequest, WorkerError, sign_vote_request_v4_with_nonce,
+    sign_withdraw_request_with_nonce,
+};
+use hon_worker_mock::MockHonWorker;
+use ic_agent::identity::AnonymousIdentity;
+use num_bigint::{BigInt, BigUint};
+
+static NEXT_NONCE: AtomicU64 = AtomicU64::new(0);
+
+fn user(id: u8) -> Principal {
+    Principal::from_slice(&[id])
+}
+
+fn worker_error<T>(res: Result<T, HonWorkerClientError>) -> WorkerError {
+    match res {
+        Err(HonWorkerClientError::Worker(e)) => e,
+        Err(e) => panic!("expected a worker error, got {e}"),
+        Ok(_) => panic!("expected a worker error"),
+    }
+}
+
+fn vote_req(post_id: &str, direction: HotOrNot, sentiment: HotOrNot) -> HoNGameVoteReqV4 {
+    let request = VoteRequestV4 {
+        publisher_principal: user(100),
+        post_id: post_id.into(),
+        vote_amount: 5,
+        direction,
+    };
+    let nonce = NEXT_NONCE.fetch_add(1, Ordering::Relaxed);
+    let signature =
+        sign_vote_request_v4_with_nonce(&AnonymousIdentity, request.clone(), nonce).unwrap();
+    HoNGameVoteReqV4 {
+        request,
+        fetched_sentiment: sentiment,
+        post_creator: Some(user(100)),
+        signature,
+        nonce: Some(nonce),
+    }
+}
+
+#[tokio::test]
+async fn balance_updates_are_compare_and_swap() {
+    let worker = MockHonWorker::start().await.unwrap();
+    let client = worker.client();
+    worker.set_balance(user(1), 100u64);
+
+    let stale = SatsBalanceUpdateRequestV2 {
+        previous_balance: BigUint::from(90u64),
+        delta: BigInt::from(10),
+        is_airdropped: false,
+    };
+    assert_eq!(
+        worker_error(client.update_balance_v2(user(1), &stale).await),
+        WorkerError::BalanceTransactionConflict {
+            new_balance: BigUint::from(100u64)
+        }
+    );
+
+    let fresh = SatsBalanceUpdateRequestV2 {
+        previous_balance: BigUint::from(100u64),
+        ..stale
+    };
+    client.update_balance_v2(user(1), &fresh).await.unwrap();
+    assert_eq!(
+        client.balance(user(1)).await.unwrap().balance,
+        BigUint::from(110u64)
+    );
+}
+
+#[tokio::test]
+async fn votes_update_balances_and_game_history() {
+    let worker = MockHonWorker::start().await.unwrap();
+    let client = worker.client();
+    worker.set_balance(user(1), 10u64);
+
+    let res = client
+        .vote_v4(user(1), &vote_req("a", HotOrNot::Hot, HotOrNot::Hot))
+        .await
+        .unwrap();
+    let GameResultV2::Win {
+        win_amt,
+        updated_balance,
+    } = res.game_result
+    else {
+        panic!("vote matching the sentiment should win");
+    };
+    assert_eq!(updated_balance, BigUint::from(10u64) + win_amt);
+
+    assert_eq!(
+        worker
+            .vote_v4(user(1), vote_req("a", HotOrNot::Not, HotOrNot::Hot))
+            .unwrap_err(),
+        WorkerError::AlreadyVotedOnPost
+    );
+
+    worker
+        .vote_v4(user(1), vote_req("b", HotOrNot::Not, HotOrNot::Hot))
+        .unwrap();
+    assert_eq!(worker.balance(user(1)), updated_balance - 5u64);
+
+    let page = client
+        .games_v4(
+            user(1),
+            &PaginatedGamesReq {
+                page_size: 1,
+                cursor: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.games.len(), 1);
+    let page = client
+        .games_v4(
+            user(1),
+            &PaginatedGamesReq {
+                page_size: 1,
+                cursor: page.next,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(page.games[0].post_id, "b");
+    assert!(page.next.is_none());
+
+    let info = client
+        .game_info_v4(
+            user(1),
+            &GameInfoReqV4 {
+                publisher_principal: user(100),
+                post_id: "a".into(),
+            },
+        )
+        .await
+        .unwrap();
+    assert!(matches!(info, Some(GameInfo::Vote { .. })));
+
+    let creator_games = worker.games(user(100));
+    assert_eq!(creator_games.len(), 2);
+    assert!(matches!(
+        creator_games[0].game_info,
+        GameInfo::CreatorReward(_)
+    ));
+}
+
+fn withdraw_req(amount: u128) -> HoNGameWithdrawReq {
+    let request = WithdrawRequest {
+        receiver: user(1),
+        amount,
+    };
+    let nonce = NEXT_NONCE.fetch_add(1, Ordering::Relaxed);
+    let signature =
+        sign_withdraw_request_with_nonce(&AnonymousIdentity, request.clone(), nonce).unwrap();
+    HoNGameWithdrawReq {
+        request,
+        signature,
+        nonce: Some(nonce),
+    }
+}
+
+#[tokio::test]
+async fn withdrawals_apply_limits_and_reject_replays() {
+    let worker = MockHonWorker::start().await.unwrap();
+    let client = worker.client();
+    worker.set_balance(user(1), 200u64);
+
+    let req = withdraw_req(50);
+    client.withdraw(user(1), &req).await.unwrap();
+    assert_eq!(worker.balance(user(1)), BigUint::from(150u64));
+
+    assert_eq!(
+        worker_error(client.withdraw(user(1), &req).await),
+        WorkerError::ReplayedRequest
+    );
+    assert_eq!(
+        worker_error(client.withdraw(user(1), &withdraw_req(10)).await),
+        WorkerError::WithdrawalBelowMinimum { min: 50 }
+    );
+    assert_eq!(
+        worker_error(client.withdraw(user(1), &withdraw_req(50)).await),
+        WorkerError::WithdrawalDailyLimitReached { remaining: 10 }
+    );
+    assert_eq!(worker.balance(user(1)), BigUint::from(150u64));
+}
@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[cfg(feature = "http")]
+    #[error("network error when fetching price: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error("{0}")]
+    Url(#[from] url::ParseError),
+    #[error("price source returned an invalid price for {0}")]
+    InvalidPrice(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
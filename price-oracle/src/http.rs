@@ -0,0 +1,54 @@
+use reqwest::{Client, StatusCode};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{Error, PriceSource, Result};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PriceResponse {
+    pub symbol: String,
+    /// Price of one whole token in USD
+    pub usd_price: Decimal,
+}
+
+/// Fetches prices from `GET {endpoint}/price/{symbol}`
+/// the endpoint responds with [`PriceResponse`], or 404 for unknown tokens
+/// note: `endpoint` must end with a trailing slash if it has a path
+#[derive(Clone, Debug)]
+pub struct HttpPriceSource {
+    endpoint: Url,
+    client: Client,
+}
+
+impl HttpPriceSource {
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            endpoint,
+            client: Client::new(),
+        }
+    }
+
+    pub fn with_client(endpoint: Url, client: Client) -> Self {
+        Self { endpoint, client }
+    }
+}
+
+impl PriceSource for HttpPriceSource {
+    async fn usd_price(&self, symbol: &str) -> Result<Option<Decimal>> {
+        let symbol = symbol.to_uppercase();
+        let url = self.endpoint.join(&format!("price/{symbol}"))?;
+
+        let res = self.client.get(url).send().await?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let price: PriceResponse = res.error_for_status()?.json().await?;
+        if price.usd_price.is_sign_negative() {
+            return Err(Error::InvalidPrice(symbol));
+        }
+
+        Ok(Some(price.usd_price))
+    }
+}
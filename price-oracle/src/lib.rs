@@ -0,0 +1,31 @@
+mod error;
+#[cfg(feature = "http")]
+mod http;
+mod static_source;
+
+pub use error::*;
+#[cfg(feature = "http")]
+pub use http::*;
+pub use static_source::*;
+
+pub use rust_decimal::{self, Decimal};
+
+pub const SATS_SYMBOL: &str = "SATS";
+pub const BTC_SYMBOL: &str = "BTC";
+pub const DOLR_SYMBOL: &str = "DOLR";
+pub const USDC_SYMBOL: &str = "USDC";
+
+/// A source of token prices in USD
+/// tokens are identified by their (case insensitive) ledger symbol, e.g. "SATS", "BTC"
+#[allow(async_fn_in_trait)]
+pub trait PriceSource {
+    /// Price of one whole token in USD
+    /// returns `None` if the source doesn't know about the token
+    async fn usd_price(&self, symbol: &str) -> Result<Option<Decimal>>;
+
+    /// Value of `amount` whole tokens in USD
+    async fn usd_value(&self, symbol: &str, amount: Decimal) -> Result<Option<Decimal>> {
+        let price = self.usd_price(symbol).await?;
+        Ok(price.map(|p| p * amount))
+    }
+}
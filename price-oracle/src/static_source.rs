@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use global_constants::{VIDEOGEN_USD_CENTS_TO_DOLR_E8S, VIDEOGEN_USD_CENTS_TO_SATS};
+use rust_decimal::Decimal;
+
+use crate::{PriceSource, Result, BTC_SYMBOL, DOLR_SYMBOL, SATS_SYMBOL, USDC_SYMBOL};
+
+const SATS_PER_BTC: u64 = 100_000_000;
+const DOLR_E8S_PER_DOLR: u64 = 100_000_000;
+
+/// In-memory price table
+/// the default table matches the fixed conversion ratios in `global_constants`
+#[derive(Clone, Debug)]
+pub struct StaticPriceSource {
+    prices: HashMap<String, Decimal>,
+}
+
+impl StaticPriceSource {
+    pub fn empty() -> Self {
+        Self {
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Set the price of one whole token in USD
+    pub fn with_price(mut self, symbol: &str, usd_price: Decimal) -> Self {
+        self.set_price(symbol, usd_price);
+        self
+    }
+
+    pub fn set_price(&mut self, symbol: &str, usd_price: Decimal) {
+        self.prices.insert(symbol.to_uppercase(), usd_price);
+    }
+
+    pub fn get_price(&self, symbol: &str) -> Option<Decimal> {
+        self.prices.get(&symbol.to_uppercase()).copied()
+    }
+}
+
+impl Default for StaticPriceSource {
+    fn default() -> Self {
+        let usd_cent = Decimal::new(1, 2);
+        let sats_price = usd_cent / Decimal::from(VIDEOGEN_USD_CENTS_TO_SATS);
+        let dolr_price = usd_cent * Decimal::from(DOLR_E8S_PER_DOLR)
+            / Decimal::from(VIDEOGEN_USD_CENTS_TO_DOLR_E8S);
+
+        Self::empty()
+            .with_price(SATS_SYMBOL, sats_price)
+            .with_price(BTC_SYMBOL, sats_price * Decimal::from(SATS_PER_BTC))
+            .with_price(DOLR_SYMBOL, dolr_price)
+            .with_price(USDC_SYMBOL, Decimal::ONE)
+    }
+}
+
+impl PriceSource for StaticPriceSource {
+    async fn usd_price(&self, symbol: &str) -> Result<Option<Decimal>> {
+        Ok(self.get_price(symbol))
+    }
+}
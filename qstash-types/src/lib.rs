@@ -2,6 +2,8 @@ use candid::Principal;
 use serde::{Deserialize, Serialize};
 use types::delegated_identity::DelegatedIdentityWire;
 
+/// Claim a user's SNS tokens out of their swap neurons
+/// the job is a no-op unless the user's `ClaimStatus` is `Unclaimed`
 #[derive(Serialize, Deserialize)]
 pub struct ClaimTokensRequest {
     pub identity: DelegatedIdentityWire,
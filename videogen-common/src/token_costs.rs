@@ -1,9 +1,13 @@
 use crate::TokenType;
 use global_constants::{
     LTX2_COST_USD_CENTS, VIDEOGEN_USD_CENTS_TO_DOLR_E8S, VIDEOGEN_USD_CENTS_TO_SATS,
+    VIDEOGEN_USD_CENTS_TO_USDC_E6S,
 };
 use std::collections::HashMap;
 use std::sync::LazyLock;
+use yral_price_oracle::{
+    rust_decimal::prelude::ToPrimitive, Decimal, PriceSource, DOLR_SYMBOL, SATS_SYMBOL, USDC_SYMBOL,
+};
 
 /// Model cost in USD cents (to avoid floating point)
 #[derive(Clone, Debug)]
@@ -26,6 +30,8 @@ pub struct TokenConversionRates {
     pub usd_cents_to_sats: u64,
     /// How many DOLR units (e8s) per USD cent
     pub usd_cents_to_dolr: u64,
+    /// How many ckUSDC units (e6s) per USD cent
+    pub usd_cents_to_usdc: u64,
 }
 
 impl Default for TokenConversionRates {
@@ -33,10 +39,41 @@ impl Default for TokenConversionRates {
         Self {
             usd_cents_to_sats: VIDEOGEN_USD_CENTS_TO_SATS, // 1 cent = 10 SATS (50 cents = 500 SATS)
             usd_cents_to_dolr: VIDEOGEN_USD_CENTS_TO_DOLR_E8S, // 1 cent = 2 DOLR = 2×10^8 e8s (50 cents = 100 DOLR)
+            usd_cents_to_usdc: VIDEOGEN_USD_CENTS_TO_USDC_E6S, // 1 cent = 0.01 USDC = 10^4 e6s
         }
     }
 }
 
+impl TokenConversionRates {
+    /// Derive conversion rates from token prices, so that videogen charges match wallet valuations
+    /// Tokens unknown to `source` keep the default rate
+    pub async fn from_price_source(source: &impl PriceSource) -> yral_price_oracle::Result<Self> {
+        let default = Self::default();
+        // 1 USD cent, in whole tokens
+        let cent_in_tokens = |usd_price: Option<Decimal>| {
+            usd_price
+                .filter(|p| *p > Decimal::ZERO)
+                .map(|p| Decimal::new(1, 2) / p)
+        };
+
+        let usd_cents_to_sats = cent_in_tokens(source.usd_price(SATS_SYMBOL).await?)
+            .and_then(|sats| sats.round().to_u64())
+            .unwrap_or(default.usd_cents_to_sats);
+        let usd_cents_to_dolr = cent_in_tokens(source.usd_price(DOLR_SYMBOL).await?)
+            .and_then(|dolr| (dolr * Decimal::from(100_000_000u64)).round().to_u64())
+            .unwrap_or(default.usd_cents_to_dolr);
+        let usd_cents_to_usdc = cent_in_tokens(source.usd_price(USDC_SYMBOL).await?)
+            .and_then(|usdc| (usdc * Decimal::from(1_000_000u64)).round().to_u64())
+            .unwrap_or(default.usd_cents_to_usdc);
+
+        Ok(Self {
+            usd_cents_to_sats,
+            usd_cents_to_dolr,
+            usd_cents_to_usdc,
+        })
+    }
+}
+
 /// Configuration for model costs and token conversions
 #[derive(Clone, Debug)]
 pub struct TokenCostConfig {
@@ -91,6 +128,7 @@ impl TokenCostConfig {
         match token_type {
             TokenType::Sats => usd_cents * self.conversion_rates.usd_cents_to_sats,
             TokenType::Dolr => usd_cents * self.conversion_rates.usd_cents_to_dolr,
+            TokenType::Usdc => usd_cents * self.conversion_rates.usd_cents_to_usdc,
             TokenType::Free => 0,
             TokenType::YralProSubscription => 1,
         }
@@ -101,6 +139,7 @@ impl TokenCostConfig {
         match token_type {
             TokenType::Sats => amount / self.conversion_rates.usd_cents_to_sats,
             TokenType::Dolr => amount / self.conversion_rates.usd_cents_to_dolr,
+            TokenType::Usdc => amount / self.conversion_rates.usd_cents_to_usdc,
             TokenType::Free => 0,
             TokenType::YralProSubscription => 0,
         }
@@ -118,5 +157,8 @@ impl TokenCostConfig {
     }
 }
 
-// Global static configuration
+/// Global static configuration, using the hard-coded conversion rates from `global_constants`
+///
+/// To charge at market prices, build a [`TokenCostConfig`] and pass
+/// [`TokenConversionRates::from_price_source`] to [`TokenCostConfig::set_conversion_rates`]
 pub static TOKEN_COST_CONFIG: LazyLock<TokenCostConfig> = LazyLock::new(TokenCostConfig::default);
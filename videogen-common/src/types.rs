@@ -64,6 +64,7 @@ pub enum TokenType {
     #[default]
     Free,
     YralProSubscription,
+    Usdc,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ToSchema, CandidType)]
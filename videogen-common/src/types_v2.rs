@@ -141,6 +141,11 @@ pub struct CostInfo {
     /// Cost in SATS (smallest unit)
     #[schema(example = 100)]
     pub sats: u64,
+
+    /// Cost in ckUSDC (e6s - smallest unit)
+    #[schema(example = 100000)]
+    #[serde(default)]
+    pub usdc: u64,
 }
 
 impl CostInfo {
@@ -153,6 +158,7 @@ impl CostInfo {
             usd_cents,
             dolr: TOKEN_COST_CONFIG.convert_usd_to_token(usd_cents, &TokenType::Dolr),
             sats: TOKEN_COST_CONFIG.convert_usd_to_token(usd_cents, &TokenType::Sats),
+            usdc: TOKEN_COST_CONFIG.convert_usd_to_token(usd_cents, &TokenType::Usdc),
         }
     }
 }